@@ -1,6 +1,6 @@
 pub(crate) fn base64_decode_array<const N: usize>(source: &str) -> Result<[u8; N], Error> {
     assert_ne!(N, 0);
-    if source.len() != (N + 2) / 3 * 4 {
+    if source.len() != N.div_ceil(3) * 4 {
         return Err(Error::WrongSize(WrongSize));
     }
     match (N % 3, &source.as_bytes()[source.len() - 2..]) {
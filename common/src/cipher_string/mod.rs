@@ -1,5 +1,5 @@
-pub(crate) struct CipherString<T> {
-    pub(crate) inner: Untyped,
+pub struct CipherString<T> {
+    pub inner: Untyped,
     data: PhantomData<fn() -> T>,
 }
 
@@ -44,7 +44,15 @@ impl<T> Debug for CipherString<T> {
 }
 
 impl<T: Stored> CipherString<T> {
-    pub(crate) fn decrypt(&self, key: &SymmetricKey) -> Result<T, DecryptError<T::DecodeError>> {
+    pub fn encrypt<R: ?Sized + Rng + CryptoRng>(
+        key: &SymmetricKey,
+        rng: &mut R,
+        value: &T,
+    ) -> Self {
+        value.encode(|bytes| Self::from(Untyped::encrypt(key, rng, bytes)))
+    }
+
+    pub fn decrypt(&self, key: &SymmetricKey) -> Result<T, DecryptError<T::DecodeError>> {
         let bytes = self.inner.decrypt(key).map_err(DecryptError::Decryption)?;
         let res = T::decode(bytes).map_err(DecryptError::Decoding)?;
         Ok(res)
@@ -52,7 +60,7 @@ impl<T: Stored> CipherString<T> {
 }
 
 #[derive(Debug)]
-pub(crate) enum DecryptError<DecodeError> {
+pub enum DecryptError<DecodeError> {
     Decryption(untyped::DecryptError),
     Decoding(DecodeError),
 }
@@ -75,7 +83,7 @@ impl<DecodeError: 'static + Error> Error for DecryptError<DecodeError> {
     }
 }
 
-pub(crate) trait Stored: Sized {
+pub trait Stored: Sized {
     fn encode<O, F: FnOnce(&[u8]) -> O>(&self, f: F) -> O;
 
     type DecodeError;
@@ -120,7 +128,7 @@ impl Stored for bool {
 }
 
 #[derive(Debug)]
-pub(crate) struct NotBoolean;
+pub struct NotBoolean;
 impl Display for NotBoolean {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         f.write_str("input was not a boolean")
@@ -128,11 +136,11 @@ impl Display for NotBoolean {
 }
 impl Error for NotBoolean {}
 
-pub(crate) use untyped::Untyped;
-mod untyped;
+pub use untyped::Untyped;
+pub mod untyped;
 
 impl CipherString<SymmetricKey> {
-    pub(crate) fn decrypt(
+    pub fn decrypt(
         &self,
         master_key: &MasterKey,
     ) -> Result<SymmetricKey, DecryptSymmetricKeyError> {
@@ -157,7 +165,7 @@ impl CipherString<SymmetricKey> {
 }
 
 #[derive(Debug)]
-pub(crate) enum DecryptSymmetricKeyError {
+pub enum DecryptSymmetricKeyError {
     WrongMasterPassword(WrongMasterPassword),
     Unpadding(block_padding::UnpadError),
     WrongSize(WrongSymmetricKeySize),
@@ -180,7 +188,7 @@ impl Error for DecryptSymmetricKeyError {
 }
 
 #[derive(Debug)]
-pub(crate) struct WrongMasterPassword;
+pub struct WrongMasterPassword;
 
 impl Display for WrongMasterPassword {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
@@ -191,7 +199,7 @@ impl Display for WrongMasterPassword {
 impl Error for WrongMasterPassword {}
 
 #[derive(Debug)]
-pub(crate) struct WrongSymmetricKeySize;
+pub struct WrongSymmetricKeySize;
 
 impl Display for WrongSymmetricKeySize {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
@@ -202,7 +210,9 @@ impl Display for WrongSymmetricKeySize {
 impl Error for WrongSymmetricKeySize {}
 
 use crate::symmetric_key::SymmetricKey;
-use rofi_bw_common::MasterKey;
+use crate::MasterKey;
+use rand::CryptoRng;
+use rand::Rng;
 use serde::Deserialize;
 use serde::Deserializer;
 use std::convert::Infallible;
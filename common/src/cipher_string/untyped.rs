@@ -1,5 +1,5 @@
 #[derive(Clone)]
-pub(crate) struct Untyped {
+pub struct Untyped {
     iv: [u8; 16],
     ciphertext: Vec<u8>,
     mac: [u8; 32],
@@ -46,10 +46,47 @@ impl FromStr for Untyped {
     }
 }
 
+impl Untyped {
+    /// Parse the binary form of a cipher string, which is used for encrypted files.
+    pub fn from_buffer(buffer: &[u8]) -> Result<Self, ParseError> {
+        let rest = match buffer {
+            [2, rest @ ..] => rest,
+            &[r#type, ..] => {
+                let type_num = Some(u32::from(r#type));
+                return Err(ParseErrorInner::UnsupportedEncryptionType(type_num).into());
+            }
+            [] => return Err(ParseErrorInner::BufferTooShort.into()),
+        };
+        if rest.len() < 16 + 32 {
+            return Err(ParseErrorInner::BufferTooShort.into());
+        }
+
+        let (iv, rest) = rest.split_at(16);
+        let (mac, ciphertext) = rest.split_at(32);
+
+        Ok(Self {
+            iv: iv.try_into().unwrap(),
+            ciphertext: ciphertext.to_owned(),
+            mac: mac.try_into().unwrap(),
+        })
+    }
+
+    /// Convert the cipher string to its binary form.
+    #[must_use]
+    pub fn to_buffer(&self) -> Vec<u8> {
+        let mut buffer = Vec::with_capacity(1 + 16 + 32 + self.ciphertext.len());
+        buffer.push(2);
+        buffer.extend_from_slice(&self.iv);
+        buffer.extend_from_slice(&self.mac);
+        buffer.extend_from_slice(&self.ciphertext);
+        buffer
+    }
+}
+
 impl<'de> Deserialize<'de> for Untyped {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
         struct Visitor;
-        impl<'de> de::Visitor<'de> for Visitor {
+        impl de::Visitor<'_> for Visitor {
             type Value = Untyped;
             fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
                 f.write_str("a cipher string")
@@ -64,7 +101,7 @@ impl<'de> Deserialize<'de> for Untyped {
 }
 
 #[derive(Debug)]
-pub(crate) struct ParseError(ParseErrorInner);
+pub struct ParseError(ParseErrorInner);
 
 impl Display for ParseError {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
@@ -84,6 +121,7 @@ enum ParseErrorInner {
     UnsupportedEncryptionType(Option<u32>),
     NotEnoughSegments,
     UnexpectedSegment,
+    BufferTooShort,
     InvalidIv(base64_decode_array::Error),
     InvalidCiphertext(base64::DecodeError),
     InvalidMac(base64_decode_array::Error),
@@ -106,6 +144,7 @@ impl Display for ParseErrorInner {
             Self::UnsupportedEncryptionType(None) => f.write_str("unsupported encryption type"),
             Self::NotEnoughSegments => f.write_str("not enough pipe-separated segments"),
             Self::UnexpectedSegment => f.write_str("unexpected pipe-separated segment at end"),
+            Self::BufferTooShort => f.write_str("buffer too short"),
             Self::InvalidIv(_) => f.write_str("IV is invalid"),
             Self::InvalidCiphertext(_) => f.write_str("ciphertext is invalid"),
             Self::InvalidMac(_) => f.write_str("MAC is invalid"),
@@ -130,7 +169,7 @@ impl From<ParseErrorInner> for ParseError {
 }
 
 impl Untyped {
-    pub(crate) fn encrypt<R: ?Sized + Rng + CryptoRng>(
+    pub fn encrypt<R: ?Sized + Rng + CryptoRng>(
         key: &SymmetricKey,
         rng: &mut R,
         plaintext: &[u8],
@@ -166,7 +205,7 @@ impl Untyped {
         Ok(())
     }
 
-    pub(crate) fn decrypt(&self, key: &SymmetricKey) -> Result<Vec<u8>, DecryptError> {
+    pub fn decrypt(&self, key: &SymmetricKey) -> Result<Vec<u8>, DecryptError> {
         self.verify(key)?;
 
         let mut buf = vec![0; self.ciphertext.len()];
@@ -180,7 +219,7 @@ impl Untyped {
 }
 
 #[derive(Debug)]
-pub(crate) enum DecryptError {
+pub enum DecryptError {
     InvalidMac(MacError),
     Unpadding(block_padding::UnpadError),
 }
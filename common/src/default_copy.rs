@@ -0,0 +1,150 @@
+//! Which field of an item is copied when it is selected from a list of items.
+//!
+//! Fields are named the way copy notifications name them, e.g. `username`, `password`, `URI`,
+//! `note`, `number` or the name of a custom field, ignoring case.
+
+/// The name of the custom field an item can use to choose its own default field, which takes
+/// precedence over the config.
+pub const ITEM_FIELD: &str = "rofi-bw-default";
+
+#[derive(Debug, Default, Clone, Deserialize, bincode::Encode, bincode::Decode)]
+#[serde(deny_unknown_fields)]
+pub struct DefaultCopy {
+    /// Tried in order before the field chosen for the item’s type.
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+
+    #[serde(default)]
+    pub login: Option<String>,
+    #[serde(default)]
+    pub secure_note: Option<String>,
+    #[serde(default)]
+    pub card: Option<String>,
+    #[serde(default)]
+    pub identity: Option<String>,
+    #[serde(default)]
+    pub ssh_key: Option<String>,
+}
+
+impl DefaultCopy {
+    /// The fields chosen for an item, most preferred first. Fields the item doesn’t have should be
+    /// skipped, falling back to the built-in default if none are left.
+    pub fn fields<'a>(
+        &'a self,
+        r#type: CipherType,
+        name: &'a str,
+        folder: Option<&'a str>,
+    ) -> impl Iterator<Item = &'a str> {
+        let by_type = match r#type {
+            CipherType::Login => &self.login,
+            CipherType::SecureNote => &self.secure_note,
+            CipherType::Card => &self.card,
+            CipherType::Identity => &self.identity,
+            CipherType::SshKey => &self.ssh_key,
+        };
+        (self.rules.iter())
+            .filter(move |rule| rule.matches(name, folder))
+            .map(|rule| &*rule.field)
+            .chain(by_type.as_deref())
+    }
+}
+
+/// Chooses the field for items whose name and folder match the given patterns. A rule without
+/// patterns matches every item.
+#[derive(Debug, Clone, Deserialize, bincode::Encode, bincode::Decode)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    #[serde(default)]
+    pub name: Option<Pattern>,
+    /// Never matches items that aren’t in a folder.
+    #[serde(default)]
+    pub folder: Option<Pattern>,
+    pub field: String,
+}
+
+impl Rule {
+    #[must_use]
+    pub fn matches(&self, name: &str, folder: Option<&str>) -> bool {
+        let name_matches = self.name.as_ref().is_none_or(|p| p.0.is_match(name));
+        let folder_matches = (self.folder.as_ref())
+            .is_none_or(|p| folder.is_some_and(|folder| p.0.is_match(folder)));
+        name_matches && folder_matches
+    }
+}
+
+/// A case-insensitive regular expression, which can match anywhere in the text unless anchored.
+#[derive(Debug, Clone)]
+pub struct Pattern(Regex);
+
+impl Pattern {
+    fn new(pattern: &str) -> Result<Self, regex::Error> {
+        RegexBuilder::new(pattern)
+            .case_insensitive(true)
+            .build()
+            .map(Self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Pattern {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        Self::new(&pattern).map_err(de::Error::custom)
+    }
+}
+
+impl bincode::Encode for Pattern {
+    fn encode<E: bincode::enc::Encoder>(
+        &self,
+        encoder: &mut E,
+    ) -> Result<(), bincode::error::EncodeError> {
+        self.0.as_str().encode(encoder)
+    }
+}
+
+impl bincode::Decode for Pattern {
+    fn decode<D: bincode::de::Decoder>(
+        decoder: &mut D,
+    ) -> Result<Self, bincode::error::DecodeError> {
+        let pattern = String::decode(decoder)?;
+        Self::new(&pattern).map_err(|e| bincode::error::DecodeError::OtherString(e.to_string()))
+    }
+}
+
+bincode::impl_borrow_decode!(Pattern);
+
+#[test]
+fn rules() {
+    let default_copy: DefaultCopy = serde_json::from_str(
+        r#"{
+            "rules": [
+                {"name": "^aws", "field": "access key"},
+                {"folder": "work", "field": "username"}
+            ],
+            "login": "totp"
+        }"#,
+    )
+    .unwrap();
+    let fields = |name, folder| {
+        let fields = default_copy.fields(CipherType::Login, name, folder);
+        fields.collect::<Vec<_>>()
+    };
+
+    assert_eq!(fields("AWS root", None), ["access key", "totp"]);
+    assert_eq!(fields("Laws", Some("Work")), ["username", "totp"]);
+    assert_eq!(fields("Email", None), ["totp"]);
+    assert_eq!(
+        default_copy
+            .fields(CipherType::Card, "AWS", None)
+            .collect::<Vec<_>>(),
+        ["access key"]
+    );
+
+    assert!(serde_json::from_str::<Rule>(r#"{"name": "(", "field": "x"}"#).is_err());
+}
+
+use crate::CipherType;
+use regex::Regex;
+use regex::RegexBuilder;
+use serde::de;
+use serde::Deserialize;
+use serde::Deserializer;
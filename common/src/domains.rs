@@ -0,0 +1,185 @@
+//! Deciding which domains belong to the same site: subdomains of the same registrable domain,
+//! according to the public suffix list, and domains the account marks as equivalent.
+
+/// The part of `host` that was registered with a registrar, e.g. `example.co.uk` for
+/// `www.example.co.uk`; `None` if `host` is itself a public suffix.
+///
+/// `host` must be in lowercase ASCII form, as `url` gives it.
+#[must_use]
+pub fn registrable(host: &str) -> Option<&str> {
+    let host = host.trim_end_matches('.');
+    let suffix_labels = public_suffix_list().suffix_labels(host);
+    if host.split('.').count() <= suffix_labels {
+        return None;
+    }
+    let start = host
+        .rmatch_indices('.')
+        .nth(suffix_labels)
+        .map_or(0, |(i, _)| i + 1);
+    Some(&host[start..])
+}
+
+/// The `domains` object of the `/sync` response.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Domains {
+    /// Groups of domains the user has marked as equivalent.
+    #[serde(default)]
+    equivalent_domains: Option<Vec<Vec<String>>>,
+    /// Groups of domains Bitwarden knows to be equivalent, some of which the user may have
+    /// excluded.
+    #[serde(default)]
+    global_equivalent_domains: Option<Vec<GlobalEquivalentDomains>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GlobalEquivalentDomains {
+    domains: Vec<String>,
+    excluded: bool,
+}
+
+impl Domains {
+    /// The registrable domains that count as the same site as `domain`, including itself.
+    #[must_use]
+    pub fn equivalent_to(&self, domain: &str) -> Vec<String> {
+        let user = self.equivalent_domains.iter().flatten();
+        let global = (self.global_equivalent_domains.iter().flatten())
+            .filter(|group| !group.excluded)
+            .map(|group| &group.domains);
+
+        let mut equivalent = vec![domain.to_owned()];
+        for group in user.chain(global) {
+            if !group.iter().any(|d| d.eq_ignore_ascii_case(domain)) {
+                continue;
+            }
+            for d in group {
+                let d = d.to_ascii_lowercase();
+                if !equivalent.contains(&d) {
+                    equivalent.push(d);
+                }
+            }
+        }
+        equivalent
+    }
+}
+
+/// The list is embedded rather than read from the system so that matching behaves the same
+/// everywhere; it can be updated from <https://publicsuffix.org/list/public_suffix_list.dat>.
+const PUBLIC_SUFFIX_LIST: &str = include_str!("../public_suffix_list.dat");
+
+fn public_suffix_list() -> &'static PublicSuffixList {
+    static LIST: OnceLock<PublicSuffixList> = OnceLock::new();
+    LIST.get_or_init(|| PublicSuffixList::parse(PUBLIC_SUFFIX_LIST))
+}
+
+struct PublicSuffixList {
+    /// The rules as written in the list, including their `*.` and `!` prefixes, with
+    /// internationalized labels converted to Punycode.
+    rules: HashSet<String>,
+}
+
+impl PublicSuffixList {
+    fn parse(list: &str) -> Self {
+        let mut rules = HashSet::new();
+        for line in list.lines() {
+            let Some(rule) = line.split_whitespace().next() else {
+                continue;
+            };
+            if rule.starts_with("//") {
+                continue;
+            }
+            let (prefix, domain) = match rule.strip_prefix('!') {
+                Some(domain) => ("!", domain),
+                None => match rule.strip_prefix("*.") {
+                    Some(domain) => ("*.", domain),
+                    None => ("", rule),
+                },
+            };
+            let Ok(Host::Domain(domain)) = Host::parse(domain) else {
+                continue;
+            };
+            rules.insert(format!("{prefix}{domain}"));
+        }
+        Self { rules }
+    }
+
+    /// How many labels at the end of `host` make up its public suffix, following the algorithm at
+    /// <https://publicsuffix.org/list/>.
+    fn suffix_labels(&self, host: &str) -> usize {
+        let labels = host.split('.').count();
+        let mut buf = String::new();
+
+        // Suffixes are tried longest first, so the first rule that matches is the one that wins.
+        let suffixes = iter::once(host).chain(host.match_indices('.').map(|(i, _)| &host[i + 1..]));
+        for (skipped, suffix) in suffixes.enumerate() {
+            let suffix_labels = labels - skipped;
+
+            buf.clear();
+            buf.push('!');
+            buf.push_str(suffix);
+            if self.rules.contains(&buf) {
+                return suffix_labels - 1;
+            }
+
+            if self.rules.contains(suffix) {
+                return suffix_labels;
+            }
+
+            if let Some((_, parent)) = suffix.split_once('.') {
+                buf.clear();
+                buf.push_str("*.");
+                buf.push_str(parent);
+                if self.rules.contains(&buf) {
+                    return suffix_labels;
+                }
+            }
+        }
+
+        // The implicit `*` rule
+        1
+    }
+}
+
+#[test]
+fn registrable_domains() {
+    assert_eq!(registrable("www.example.com"), Some("example.com"));
+    assert_eq!(registrable("example.com"), Some("example.com"));
+    assert_eq!(registrable("com"), None);
+    assert_eq!(registrable("a.b.example.co.uk"), Some("example.co.uk"));
+    assert_eq!(registrable("co.uk"), None);
+    assert_eq!(registrable("foo.bar.github.io"), Some("bar.github.io"));
+    assert_eq!(registrable("localhost"), None);
+    assert_eq!(registrable("a.b.unknowntld"), Some("b.unknowntld"));
+    // `*.ck` with the exception `!www.ck`
+    assert_eq!(registrable("a.b.ck"), Some("a.b.ck"));
+    assert_eq!(registrable("a.www.ck"), Some("www.ck"));
+}
+
+#[test]
+fn equivalent_domains() {
+    let domains: Domains = serde_json::from_str(
+        r#"{
+            "equivalentDomains": [["example.com", "example.org"]],
+            "globalEquivalentDomains": [
+                {"type": 0, "domains": ["google.com", "youtube.com"], "excluded": false},
+                {"type": 1, "domains": ["example.com", "example.net"], "excluded": true}
+            ]
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        domains.equivalent_to("example.com"),
+        ["example.com", "example.org"]
+    );
+    assert_eq!(
+        domains.equivalent_to("youtube.com"),
+        ["youtube.com", "google.com"]
+    );
+    assert_eq!(domains.equivalent_to("example.net"), ["example.net"]);
+}
+
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::iter;
+use std::sync::OnceLock;
+use url::Host;
@@ -3,23 +3,43 @@
 pub const PIPE_FD_ENV_VAR: &str = "ROFI_BW_PIPE_FD";
 
 pub use handshake::Handshake;
+pub use handshake::SearchFields;
 pub mod handshake {
     #[derive(Clone, Copy, bincode::Encode, bincode::Decode)]
-    pub struct Handshake<MasterKeyT, DataT, HistoryT> {
+    pub struct Handshake<MasterKeyT, DataT, HistoryT, UsageT, KeybindsT, DefaultCopyT> {
         pub master_key: MasterKeyT,
         pub data: DataT,
         pub history: HistoryT,
+        pub usage: UsageT,
+        pub sort: Sort,
+        /// The number of seconds until the vault locks whatever the user does, if it ever will.
+        pub lock_in: Option<u64>,
+        pub search_fields: SearchFields,
+        /// Used both by the plugin and for the arguments rofi is run with, so that they agree.
+        pub keybinds: KeybindsT,
+        pub default_copy: DefaultCopyT,
     }
 
-    pub fn write<W, MasterKeyT, DataT, HistoryT>(
+    /// Which fields of an item the filter matches against in lists of items, besides its name.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, bincode::Encode, bincode::Decode)]
+    pub struct SearchFields {
+        pub uris: bool,
+        pub username: bool,
+        pub folder: bool,
+    }
+
+    pub fn write<W, MasterKeyT, DataT, HistoryT, UsageT, KeybindsT, DefaultCopyT>(
         mut writer: W,
-        handshake: &Handshake<MasterKeyT, DataT, HistoryT>,
+        handshake: &Handshake<MasterKeyT, DataT, HistoryT, UsageT, KeybindsT, DefaultCopyT>,
     ) -> Result<(), WriteError>
     where
         W: io::Write,
         MasterKeyT: Borrow<MasterKey> + bincode::Encode,
         DataT: Borrow<[u8]> + bincode::Encode,
         HistoryT: Borrow<History<View>> + bincode::Encode,
+        UsageT: Borrow<usage::Record> + bincode::Encode,
+        KeybindsT: Borrow<menu_keybinds::Combinations> + bincode::Encode,
+        DefaultCopyT: Borrow<DefaultCopy> + bincode::Encode,
     {
         let config = bincode::config::standard();
         bincode::encode_into_std_write(handshake, &mut writer, config).map_err(WriteError)?;
@@ -41,7 +61,14 @@ pub mod handshake {
         }
     }
 
-    type Owned = Handshake<MasterKey, Box<[u8]>, History<View>>;
+    type Owned = Handshake<
+        MasterKey,
+        Box<[u8]>,
+        History<View>,
+        usage::Record,
+        menu_keybinds::Combinations,
+        DefaultCopy,
+    >;
     pub fn read<R: io::BufRead>(mut reader: R) -> Result<Owned, ReadError> {
         let config = bincode::config::standard();
         bincode::decode_from_std_read(&mut reader, config).map_err(ReadError)
@@ -63,6 +90,10 @@ pub mod handshake {
     }
 
     use super::View;
+    use crate::default_copy::DefaultCopy;
+    use crate::menu_keybinds;
+    use crate::usage;
+    use crate::usage::Sort;
     use crate::MasterKey;
     use rofi_bw_util::History;
     use std::borrow::Borrow;
@@ -84,17 +115,55 @@ pub mod menu_request {
         Copy {
             /// Used in notifications and for the reprompt message
             cipher_name: String,
+            /// Used to record that the item was used; `None` when copying from a Send.
+            cipher_id: Option<[u8; 16]>,
             field: String,
             data: String,
+            /// Fields copied in turn after the previous one has been pasted, replacing it.
+            then: Vec<CopyStep>,
+            /// Whether clipboard managers should be asked not to record the data.
+            secret: bool,
+            /// Whether to copy to the selection other than the configured one, e.g. the primary
+            /// selection instead of the clipboard.
+            other_selection: bool,
             /// Used in notifications
             image_path: Option<String>,
             reprompt: bool,
             /// When a reprompt is cancelled the old menu state should be restored.
             menu_state: MenuState,
         },
+        SaveAttachment {
+            /// Used in notifications and for the reprompt message
+            cipher_name: String,
+            cipher_id: [u8; 16],
+            attachment_id: String,
+            file_name: String,
+            /// The attachment’s key, still encrypted with the user key. `None` for very old
+            /// attachments that are encrypted with the user key directly.
+            key: Option<String>,
+            reprompt: bool,
+            menu_state: MenuState,
+        },
+        CreateSend {
+            name: String,
+            text: String,
+            /// Whether the text should be hidden from the recipient by default.
+            hidden: bool,
+            /// The name of the cipher the text came from, if its reprompt must be passed first.
+            reprompt_cipher: Option<String>,
+            menu_state: MenuState,
+        },
         Sync {
             menu_state: MenuState,
         },
+        EditFolder {
+            edit: FolderEdit,
+            menu_state: MenuState,
+        },
+        EditSend {
+            edit: SendEdit,
+            menu_state: MenuState,
+        },
         Lock,
         LogOut,
         Exit {
@@ -102,6 +171,46 @@ pub mod menu_request {
         },
     }
 
+    impl MenuRequest {
+        /// The state of the menu when the request was made, for the requests that carry it.
+        #[must_use]
+        pub fn menu_state(&self) -> Option<&MenuState> {
+            match self {
+                Self::Copy { menu_state, .. }
+                | Self::SaveAttachment { menu_state, .. }
+                | Self::CreateSend { menu_state, .. }
+                | Self::Sync { menu_state }
+                | Self::EditFolder { menu_state, .. }
+                | Self::EditSend { menu_state, .. }
+                | Self::Exit { menu_state } => Some(menu_state),
+                Self::Lock | Self::LogOut => None,
+            }
+        }
+    }
+
+    /// A field copied after an earlier one in a sequence.
+    #[derive(Debug, Clone, bincode::Encode, bincode::Decode)]
+    pub struct CopyStep {
+        pub field: String,
+        pub data: String,
+    }
+
+    /// A change to make to the user’s folders. Names are sent in plain text and encrypted by the
+    /// parent process, like the contents of Sends.
+    #[derive(Debug, Clone, bincode::Encode, bincode::Decode)]
+    pub enum FolderEdit {
+        Create { name: String },
+        Rename { id: [u8; 16], name: String },
+        Delete { id: [u8; 16] },
+    }
+
+    /// A change to make to one of the user’s Sends.
+    #[derive(Debug, Clone, bincode::Encode, bincode::Decode)]
+    pub enum SendEdit {
+        SetDisabled { id: [u8; 16], disabled: bool },
+        Delete { id: [u8; 16] },
+    }
+
     pub fn write<W>(mut writer: W, menu_request: &MenuRequest) -> Result<(), WriteError>
     where
         W: io::Write,
@@ -151,6 +260,8 @@ pub mod menu_request {
     pub struct MenuState {
         pub filter: String,
         pub history: History<View>,
+        /// The UUIDs of the items opened in the menu, in order, to be recorded as recently used.
+        pub opened: Vec<[u8; 16]>,
     }
 
     use super::View;
@@ -168,6 +279,10 @@ pub enum View {
     NoFolder,
     Folder(Filter),
     Cipher(Filter),
+    PasswordHistory(Filter),
+    Send([u8; 16]),
+    /// The logins with a URI that matches this one.
+    Uri(String),
 }
 
 impl Default for View {
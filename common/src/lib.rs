@@ -7,7 +7,12 @@
     unused_lifetimes,
     unused_qualifications
 )]
-#![allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
+#![allow(
+    clippy::missing_errors_doc,
+    clippy::missing_panics_doc,
+    clippy::single_char_pattern,
+    clippy::match_bool
+)]
 
 pub use master_key::MasterKey;
 mod master_key {
@@ -63,15 +68,47 @@ mod master_key {
 
 pub mod ipc;
 
+pub use symmetric_key::SymmetricKey;
+mod symmetric_key;
+
+pub use cipher_string::CipherString;
+pub mod cipher_string;
+
+use base64_decode_array::base64_decode_array;
+mod base64_decode_array;
+
+pub mod send;
+
+pub mod domains;
+
+pub mod uri_match;
+
+pub mod usage;
+
+pub mod default_copy;
+
+pub mod totp;
+
 pub use keybind::Keybind;
 pub mod keybind {
-    #[derive(Clone, Copy)]
+    #[derive(Clone)]
     pub struct Keybind<Action> {
-        pub combination: &'static str,
+        /// Empty if the keybind is disabled.
+        pub combination: Cow<'static, str>,
         pub action: Action,
         pub description: &'static str,
     }
 
+    impl<Action> Keybind<Action> {
+        #[must_use]
+        pub fn is_enabled(&self) -> bool {
+            !self.combination.is_empty()
+        }
+    }
+
+    /// The number of custom keybinds rofi supports.
+    pub const MAX_ENABLED: usize = 19;
+
     pub struct HelpMarkup<'keybinds, Action>(pub &'keybinds [Keybind<Action>]);
 
     impl<Action> Display for HelpMarkup<'_, Action> {
@@ -86,17 +123,21 @@ pub mod keybind {
         }
     }
 
+    /// Give each enabled keybind the next custom keybind of rofi, so that the `n`th enabled keybind
+    /// is reported as custom command `n`.
     pub fn apply_to_command<Action>(command: &mut process::Command, keybinds: &[Keybind<Action>]) {
-        assert!(keybinds.len() <= 19);
+        let enabled = keybinds.iter().filter(|keybind| keybind.is_enabled());
+        assert!(enabled.clone().count() <= MAX_ENABLED);
 
         let mut arg_name_buf = String::new();
-        for (i, keybind) in keybinds.iter().enumerate() {
+        for (i, keybind) in enabled.enumerate() {
             arg_name_buf.clear();
             write!(arg_name_buf, "-kb-custom-{}", i + 1).unwrap();
-            command.arg(&*arg_name_buf).arg(keybind.combination);
+            command.arg(&*arg_name_buf).arg(&*keybind.combination);
         }
     }
 
+    use std::borrow::Cow;
     use std::fmt;
     use std::fmt::Display;
     use std::fmt::Formatter;
@@ -114,6 +155,23 @@ pub mod menu_keybinds {
         Lock,
         LogOut,
         Navigate(Navigate),
+        RenameFolder,
+        CreateSend,
+        Copy(QuickCopy),
+        /// Copy the selected field or item like Enter, but to the selection other than the
+        /// configured one.
+        CopyOtherSelection,
+    }
+
+    /// A field that can be copied from a list of items without opening the item.
+    #[derive(Clone, Copy)]
+    pub enum QuickCopy {
+        Username,
+        Password,
+        Totp,
+        Uri,
+        /// The username, then the password once the username has been pasted.
+        Sequence,
     }
 
     #[derive(Clone, Copy)]
@@ -122,98 +180,286 @@ pub mod menu_keybinds {
         Forward,
     }
 
-    /// The keybindings, ordered by their custom command number.
+    /// The row of the keybind help that a keybind is displayed in.
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    pub enum Row {
+        Session,
+        /// Keybinds that select a category (e.g. all, trash) to be shown.
+        Lists,
+        /// Keybinds that select a specific type bucket to be shown.
+        TypeBuckets,
+        Navigate,
+        Folder,
+        Send,
+        QuickCopy,
+        OtherSelection,
+    }
+
+    impl Action {
+        #[must_use]
+        pub const fn row(self) -> Row {
+            match self {
+                Self::Sync | Self::Lock | Self::LogOut => Row::Session,
+                Self::ShowList(List::TypeBucket(_)) => Row::TypeBuckets,
+                Self::ShowList(_) | Self::Parent => Row::Lists,
+                Self::Navigate(_) => Row::Navigate,
+                Self::RenameFolder => Row::Folder,
+                Self::CreateSend => Row::Send,
+                Self::Copy(_) => Row::QuickCopy,
+                Self::CopyOtherSelection => Row::OtherSelection,
+            }
+        }
+
+        /// The name of the action in the `[keybinds]` section of the config file.
+        #[must_use]
+        pub const fn name(self) -> &'static str {
+            match self {
+                Self::ShowList(List::All) => "all",
+                Self::ShowList(List::Recent) => "recent",
+                Self::ShowList(List::Trash) => "trash",
+                Self::ShowList(List::Favourites) => "favourites",
+                Self::ShowList(List::TypeBucket(CipherType::Login)) => "logins",
+                Self::ShowList(List::TypeBucket(CipherType::SecureNote)) => "secure_notes",
+                Self::ShowList(List::TypeBucket(CipherType::Card)) => "cards",
+                Self::ShowList(List::TypeBucket(CipherType::Identity)) => "identities",
+                Self::ShowList(List::TypeBucket(CipherType::SshKey)) => "ssh_keys",
+                Self::ShowList(List::Folders) => "folders",
+                Self::ShowList(List::Sends) => "sends",
+                Self::Parent => "parent",
+                Self::Sync => "sync",
+                Self::Lock => "lock",
+                Self::LogOut => "log_out",
+                Self::Navigate(Navigate::Back) => "back",
+                Self::Navigate(Navigate::Forward) => "forward",
+                Self::RenameFolder => "rename_folder",
+                Self::CreateSend => "send",
+                Self::Copy(QuickCopy::Username) => "copy_username",
+                Self::Copy(QuickCopy::Password) => "copy_password",
+                Self::Copy(QuickCopy::Totp) => "copy_totp",
+                Self::Copy(QuickCopy::Uri) => "copy_uri",
+                Self::Copy(QuickCopy::Sequence) => "copy_sequence",
+                Self::CopyOtherSelection => "copy_other_selection",
+            }
+        }
+    }
+
+    /// The key combination of each of `MENU_KEYBINDS`, in order, after applying the user’s
+    /// configuration. An empty combination disables the keybind.
+    #[derive(Debug, Clone, PartialEq, Eq, bincode::Encode, bincode::Decode)]
+    pub struct Combinations(Vec<String>);
+
+    impl Default for Combinations {
+        fn default() -> Self {
+            Self(
+                MENU_KEYBINDS
+                    .iter()
+                    .map(|keybind| keybind.combination.clone().into_owned())
+                    .collect(),
+            )
+        }
+    }
+
+    impl Combinations {
+        /// Set the combination of the action with the given name. Returns `false` if there is no
+        /// such action.
+        ///
+        /// At most `keybind::MAX_ENABLED` keybinds can be enabled at once, which
+        /// [`Self::enabled`] can be used to check.
+        pub fn set(&mut self, name: &str, combination: String) -> bool {
+            let Some(i) = MENU_KEYBINDS.iter().position(|k| k.action.name() == name) else {
+                return false;
+            };
+            self.0[i] = combination;
+            true
+        }
+
+        /// The number of keybinds that are enabled.
+        #[must_use]
+        pub fn enabled(&self) -> usize {
+            self.0
+                .iter()
+                .filter(|combination| !combination.is_empty())
+                .count()
+        }
+
+        /// `MENU_KEYBINDS` with these combinations.
+        #[must_use]
+        pub fn keybinds(&self) -> Vec<Keybind<Action>> {
+            MENU_KEYBINDS
+                .iter()
+                .zip(&self.0)
+                .map(|(keybind, combination)| Keybind {
+                    combination: Cow::Owned(combination.clone()),
+                    ..keybind.clone()
+                })
+                .collect()
+        }
+    }
+
+    /// The keybindings. The enabled ones are given custom command numbers in this order.
+    ///
+    /// Rofi only has enough custom keybinds for those enabled by default, so the rest are disabled
+    /// until the user configures them.
     pub const MENU_KEYBINDS: &[Keybind<Action>] = &[
         Keybind {
-            combination: "Control+s",
+            combination: Cow::Borrowed("Control+s"),
             action: Action::Sync,
             description: "Sync",
         },
         Keybind {
-            combination: "Control+q",
+            combination: Cow::Borrowed("Control+q"),
             action: Action::Lock,
             description: "Lock",
         },
         Keybind {
-            combination: "Control+o",
+            combination: Cow::Borrowed("Control+o"),
             action: Action::LogOut,
             description: "Log out",
         },
         Keybind {
-            combination: "Alt+a",
+            combination: Cow::Borrowed("Alt+a"),
             action: Action::ShowList(List::All),
             description: "All",
         },
         Keybind {
-            combination: "Alt+t",
+            combination: Cow::Borrowed(""),
+            action: Action::ShowList(List::Recent),
+            description: "Recent",
+        },
+        Keybind {
+            combination: Cow::Borrowed("Alt+t"),
             action: Action::ShowList(List::Trash),
             description: "Trash",
         },
         Keybind {
-            combination: "Alt+v",
+            combination: Cow::Borrowed("Alt+v"),
             action: Action::ShowList(List::Favourites),
             description: "Favourites",
         },
         Keybind {
-            combination: "Alt+d",
+            combination: Cow::Borrowed("Alt+d"),
             action: Action::ShowList(List::Folders),
             description: "Folders",
         },
         Keybind {
-            combination: "Alt+p",
+            combination: Cow::Borrowed(""),
+            action: Action::ShowList(List::Sends),
+            description: "Sends",
+        },
+        Keybind {
+            combination: Cow::Borrowed("Alt+p"),
             action: Action::Parent,
             description: "Parent",
         },
         Keybind {
-            combination: "Alt+g",
+            combination: Cow::Borrowed("Alt+g"),
             action: Action::ShowList(List::TypeBucket(CipherType::Login)),
             description: "Logins",
         },
         Keybind {
-            combination: "Alt+n",
+            combination: Cow::Borrowed("Alt+n"),
             action: Action::ShowList(List::TypeBucket(CipherType::SecureNote)),
             description: "Secure notes",
         },
         Keybind {
-            combination: "Alt+c",
+            combination: Cow::Borrowed("Alt+c"),
             action: Action::ShowList(List::TypeBucket(CipherType::Card)),
             description: "Cards",
         },
         Keybind {
-            combination: "Alt+i",
+            combination: Cow::Borrowed("Alt+i"),
             action: Action::ShowList(List::TypeBucket(CipherType::Identity)),
             description: "Identities",
         },
         Keybind {
-            combination: "Alt+h",
+            combination: Cow::Borrowed(""),
+            action: Action::ShowList(List::TypeBucket(CipherType::SshKey)),
+            description: "SSH keys",
+        },
+        Keybind {
+            combination: Cow::Borrowed("Alt+h"),
             action: Action::Navigate(Navigate::Back),
             description: "Back",
         },
         Keybind {
-            combination: "Alt+l",
+            combination: Cow::Borrowed("Alt+l"),
             action: Action::Navigate(Navigate::Forward),
             description: "Forward",
         },
+        Keybind {
+            combination: Cow::Borrowed("Alt+r"),
+            action: Action::RenameFolder,
+            description: "Rename folder to input",
+        },
+        Keybind {
+            combination: Cow::Borrowed("Alt+s"),
+            action: Action::CreateSend,
+            description: "Send selected field or input",
+        },
+        Keybind {
+            combination: Cow::Borrowed("Alt+u"),
+            action: Action::Copy(QuickCopy::Username),
+            description: "Copy username",
+        },
+        Keybind {
+            combination: Cow::Borrowed("Alt+w"),
+            action: Action::Copy(QuickCopy::Password),
+            description: "Copy password",
+        },
+        Keybind {
+            combination: Cow::Borrowed("Alt+o"),
+            action: Action::Copy(QuickCopy::Totp),
+            description: "Copy TOTP",
+        },
+        Keybind {
+            combination: Cow::Borrowed(""),
+            action: Action::Copy(QuickCopy::Uri),
+            description: "Copy URI",
+        },
+        Keybind {
+            combination: Cow::Borrowed(""),
+            action: Action::Copy(QuickCopy::Sequence),
+            description: "Copy username, then password",
+        },
+        Keybind {
+            combination: Cow::Borrowed(""),
+            action: Action::CopyOtherSelection,
+            description: "Copy to other selection",
+        },
     ];
 
-    /// Call the given callback with each row of keybinds to display.
-    pub fn keybinds_ui<F: FnMut(&'static [Keybind<Action>]), HistoryItem: PartialEq>(
+    /// Call the given callback with each row of keybinds to display, leaving out those that are
+    /// disabled. `keybinds` is ordered like `MENU_KEYBINDS`, which keeps the keybinds of each
+    /// [`Row`] together.
+    ///
+    /// `viewing_folders` should be set when the folder list is being shown, so that the keybinds
+    /// which edit folders are displayed, and likewise `viewing_items` for lists of items and the
+    /// keybinds which copy from them.
+    pub fn keybinds_ui<F: FnMut(&[Keybind<Action>]), HistoryItem: PartialEq>(
+        keybinds: &[Keybind<Action>],
         history: Option<&History<HistoryItem>>,
+        viewing_folders: bool,
+        viewing_items: bool,
         mut f: F,
     ) {
-        f(&MENU_KEYBINDS[0..3]);
-        // If the history isn’t `Some`, the menu hasn’t initialized.
-        if let Some(history) = history {
-            // Keybinds that select a category (e.g. all, trash) to be shown.
-            f(&MENU_KEYBINDS[3..8]);
-            // Keybinds that select a specific type bucket to be shown.
-            f(&MENU_KEYBINDS[8..12]);
-            // Back and forward keybinds
-            match (history.can_go_back(), history.can_go_forward()) {
-                (false, false) => {}
-                (false, true) => f(&MENU_KEYBINDS[13..14]),
-                (true, false) => f(&MENU_KEYBINDS[12..13]),
-                (true, true) => f(&MENU_KEYBINDS[12..14]),
+        let is_shown = |action: Action| match (action, history) {
+            _ if action.row() == Row::Session => true,
+            // If the history isn’t `Some`, the menu hasn’t initialized.
+            (_, None) => false,
+            (Action::Navigate(Navigate::Back), Some(history)) => history.can_go_back(),
+            (Action::Navigate(Navigate::Forward), Some(history)) => history.can_go_forward(),
+            (Action::RenameFolder, Some(_)) => viewing_folders,
+            (Action::Copy(_), Some(_)) => viewing_items,
+            (_, Some(_)) => true,
+        };
+
+        for row in keybinds.chunk_by(|a, b| a.action.row() == b.action.row()) {
+            let row = (row.iter())
+                .filter(|keybind| keybind.is_enabled() && is_shown(keybind.action))
+                .cloned()
+                .collect::<Vec<_>>();
+            if !row.is_empty() {
+                f(&row);
             }
         }
     }
@@ -222,6 +468,7 @@ pub mod menu_keybinds {
     use crate::Keybind;
     use crate::List;
     use rofi_bw_util::History;
+    use std::borrow::Cow;
 }
 
 pub use list::List;
@@ -229,10 +476,13 @@ mod list {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, bincode::Encode, bincode::Decode)]
     pub enum List {
         All,
+        /// The items most recently copied from or opened, most recent first.
+        Recent,
         Trash,
         Favourites,
         TypeBucket(CipherType),
         Folders,
+        Sends,
     }
 
     impl List {
@@ -240,13 +490,16 @@ mod list {
         pub const fn description(self) -> &'static str {
             match self {
                 Self::All => "All items",
+                Self::Recent => "Recently used",
                 Self::Trash => "Trash",
                 Self::Favourites => "Favourites",
                 Self::TypeBucket(CipherType::Login) => "Logins",
                 Self::TypeBucket(CipherType::SecureNote) => "Secure notes",
                 Self::TypeBucket(CipherType::Card) => "Cards",
                 Self::TypeBucket(CipherType::Identity) => "Identities",
+                Self::TypeBucket(CipherType::SshKey) => "SSH keys",
                 Self::Folders => "Folders",
+                Self::Sends => "Sends",
             }
         }
     }
@@ -260,4 +513,5 @@ pub enum CipherType {
     SecureNote = 1,
     Card = 2,
     Identity = 3,
+    SshKey = 4,
 }
@@ -0,0 +1,14 @@
+//! Utilities shared between the parts of rofi-bw that deal with Bitwarden Send.
+
+/// The length of the key material that Send keys are derived from.
+pub const KEY_MATERIAL_LEN: usize = 16;
+
+/// Get the link that a Send can be accessed at by its recipients.
+#[must_use]
+pub fn link(access_id: &str, key_material: &[u8]) -> String {
+    let key = base64::encode_config(key_material, base64::URL_SAFE_NO_PAD);
+    format!("https://send.bitwarden.com/#{access_id}/{key}")
+}
+
+/// The name given to Sends whose name was not specified.
+pub const DEFAULT_NAME: &str = "Sent from rofi-bw";
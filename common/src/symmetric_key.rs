@@ -0,0 +1,48 @@
+#[derive(Clone)]
+pub struct SymmetricKey(pub Zeroizing<[u8; Self::LEN]>);
+
+impl SymmetricKey {
+    pub const LEN: usize = 64;
+    #[must_use]
+    pub fn stretch_master(master_key: &MasterKey) -> Self {
+        let mut key = Self::zeroed();
+        let hkdf = <Hkdf<Sha256>>::from_prk(&*master_key.0).unwrap();
+        hkdf.expand(b"enc", &mut key.0[0..32]).unwrap();
+        hkdf.expand(b"mac", &mut key.0[32..64]).unwrap();
+        key
+    }
+    /// Derive the key of a Send from its key material.
+    #[must_use]
+    pub fn for_send(key_material: &[u8]) -> Self {
+        let mut key = Self::zeroed();
+        let hkdf = <Hkdf<Sha256>>::new(Some(b"bitwarden-send"), key_material);
+        hkdf.expand(b"send", &mut *key.0).unwrap();
+        key
+    }
+    #[must_use]
+    pub fn zeroed() -> Self {
+        Self(Zeroizing::new([0; Self::LEN]))
+    }
+    #[must_use]
+    pub fn from_slice(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::LEN {
+            return None;
+        }
+        let mut key = Self::zeroed();
+        key.0.copy_from_slice(bytes);
+        Some(key)
+    }
+    #[must_use]
+    pub fn encryption_key(&self) -> &[u8; 32] {
+        self.0[0..32].try_into().unwrap()
+    }
+    #[must_use]
+    pub fn mac_key(&self) -> &[u8; 32] {
+        self.0[32..64].try_into().unwrap()
+    }
+}
+
+use crate::MasterKey;
+use hkdf::Hkdf;
+use sha2::Sha256;
+use zeroize::Zeroizing;
@@ -0,0 +1,170 @@
+//! Generation of TOTP codes from the authenticator keys stored in logins.
+
+/// Generate the code for the given authenticator key at the given time.
+///
+/// The key can be a bare Base32 secret, an `otpauth://` URI or a `steam://` URI, like in the
+/// official clients.
+pub fn generate(key: &str, time: SystemTime) -> anyhow::Result<String> {
+    let params = Params::parse(key.trim()).context("invalid authenticator key")?;
+
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let counter = seconds / params.period;
+
+    let hash = match params.algorithm {
+        Algorithm::Sha1 => sign::<Hmac<Sha1>>(&params.secret, counter),
+        Algorithm::Sha256 => sign::<Hmac<Sha256>>(&params.secret, counter),
+        Algorithm::Sha512 => sign::<Hmac<Sha512>>(&params.secret, counter),
+    };
+
+    // Dynamic truncation, as described in RFC 4226.
+    let offset = usize::from(hash[hash.len() - 1] & 0xF);
+    let mut code = u32::from_be_bytes(hash[offset..offset + 4].try_into().unwrap()) & 0x7FFF_FFFF;
+
+    Ok(match params.format {
+        Format::Digits(digits) => {
+            let code = code % 10_u32.pow(digits);
+            format!("{code:0width$}", width = digits as usize)
+        }
+        Format::Steam => {
+            const ALPHABET: &[u8; 26] = b"23456789BCDFGHJKMNPQRTVWXY";
+            let mut s = String::with_capacity(5);
+            for _ in 0..5 {
+                s.push(char::from(ALPHABET[(code % 26) as usize]));
+                code /= 26;
+            }
+            s
+        }
+    })
+}
+
+struct Params {
+    secret: Zeroizing<Vec<u8>>,
+    algorithm: Algorithm,
+    format: Format,
+    period: u64,
+}
+
+enum Algorithm {
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+enum Format {
+    Digits(u32),
+    Steam,
+}
+
+impl Params {
+    fn parse(key: &str) -> anyhow::Result<Self> {
+        let mut params = Self {
+            secret: Zeroizing::new(Vec::new()),
+            algorithm: Algorithm::Sha1,
+            format: Format::Digits(6),
+            period: 30,
+        };
+
+        if let Some(secret) = key.strip_prefix("steam://") {
+            params.secret = decode_base32(secret).context("secret is not valid Base32")?;
+            params.format = Format::Steam;
+        } else if key.starts_with("otpauth://") {
+            let url = Url::parse(key).context("failed to parse otpauth URI")?;
+            let mut secret = None;
+            for (name, value) in url.query_pairs() {
+                match &*name {
+                    "secret" => secret = Some(decode_base32(&value)),
+                    "algorithm" => {
+                        params.algorithm = match &*value.to_ascii_uppercase() {
+                            "SHA1" => Algorithm::Sha1,
+                            "SHA256" => Algorithm::Sha256,
+                            "SHA512" => Algorithm::Sha512,
+                            _ => anyhow::bail!("unsupported algorithm {value}"),
+                        };
+                    }
+                    "digits" => {
+                        let digits = value.parse().context("digits is not a number")?;
+                        anyhow::ensure!((1..=10).contains(&digits), "{digits} digits is invalid");
+                        params.format = Format::Digits(digits);
+                    }
+                    "period" => {
+                        let period = value.parse().context("period is not a number")?;
+                        anyhow::ensure!(period > 0, "period must not be zero");
+                        params.period = period;
+                    }
+                    _ => {}
+                }
+            }
+            params.secret = secret
+                .context("otpauth URI has no secret")?
+                .context("secret is not valid Base32")?;
+        } else {
+            params.secret = decode_base32(key).context("secret is not valid Base32")?;
+        }
+
+        anyhow::ensure!(!params.secret.is_empty(), "secret is empty");
+
+        Ok(params)
+    }
+}
+
+/// Decode Base32 leniently, since authenticator keys are often written in lowercase or with spaces
+/// and padding.
+fn decode_base32(s: &str) -> Option<Zeroizing<Vec<u8>>> {
+    let mut decoded = Zeroizing::new(Vec::with_capacity(s.len() * 5 / 8));
+    let mut buffer = 0_u32;
+    let mut bits = 0;
+
+    for c in s.chars() {
+        let value = match c.to_ascii_uppercase() {
+            c @ 'A'..='Z' => u32::from(c) - u32::from('A'),
+            c @ '2'..='7' => u32::from(c) - u32::from('2') + 26,
+            ' ' | '-' | '=' => continue,
+            _ => return None,
+        };
+        buffer = (buffer << 5 | value) & 0xFFFF;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            decoded.push((buffer >> bits).to_le_bytes()[0]);
+        }
+    }
+
+    Some(decoded)
+}
+
+fn sign<M: Mac + KeyInit>(secret: &[u8], counter: u64) -> Vec<u8> {
+    let mut mac = <M as Mac>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(&counter.to_be_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[test]
+fn rfc_6238() {
+    // The SHA-1 test vectors from RFC 6238.
+    let key = "otpauth://totp/test?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&digits=8";
+    let at = |seconds| generate(key, UNIX_EPOCH + Duration::from_secs(seconds)).unwrap();
+    assert_eq!(at(59), "94287082");
+    assert_eq!(at(1_111_111_109), "07081804");
+    assert_eq!(at(2_000_000_000), "69279037");
+
+    let bare = "gezd gnbv gy3t qojq gezd gnbv gy3t qojq";
+    let time = UNIX_EPOCH + Duration::from_secs(59);
+    assert_eq!(generate(bare, time).unwrap(), "287082");
+}
+
+use anyhow::Context as _;
+use hmac::digest::KeyInit;
+use hmac::Hmac;
+use hmac::Mac;
+use sha1::Sha1;
+use sha2::Sha256;
+use sha2::Sha512;
+#[cfg(test)]
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+use url::Url;
+use zeroize::Zeroizing;
@@ -0,0 +1,185 @@
+//! Matching logins’ URIs against the URI of a page, following each URI’s match detection setting
+//! the same way Bitwarden’s autofill does.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UriMatchType {
+    Domain = 0,
+    Host = 1,
+    StartsWith = 2,
+    Exact = 3,
+    RegularExpression = 4,
+    Never = 5,
+}
+
+impl<'de> Deserialize<'de> for UriMatchType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+        impl de::Visitor<'_> for Visitor {
+            type Value = UriMatchType;
+            fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                f.write_str("a URI match type")
+            }
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                Ok(match v {
+                    0 => UriMatchType::Domain,
+                    1 => UriMatchType::Host,
+                    2 => UriMatchType::StartsWith,
+                    3 => UriMatchType::Exact,
+                    4 => UriMatchType::RegularExpression,
+                    5 => UriMatchType::Never,
+                    _ => return Err(de::Error::invalid_value(de::Unexpected::Unsigned(v), &self)),
+                })
+            }
+        }
+        deserializer.deserialize_u64(Visitor)
+    }
+}
+
+/// The page that logins are being matched against.
+pub struct Page {
+    uri: String,
+    host: Option<String>,
+    /// The registrable domain of the page followed by those equivalent to it.
+    domains: Vec<String>,
+}
+
+impl Page {
+    #[must_use]
+    pub fn new(uri: String, domains: &Domains) -> Self {
+        let url = parse(&uri);
+        Self {
+            host: url.as_ref().and_then(host),
+            domains: url
+                .as_ref()
+                .and_then(domain)
+                .map_or_else(Vec::new, |domain| domains.equivalent_to(&domain)),
+            uri,
+        }
+    }
+
+    #[must_use]
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    /// Whether a login URI matches this page. Logins without a match detection setting use
+    /// `Domain`, Bitwarden’s default.
+    #[must_use]
+    pub fn matches(&self, login_uri: &str, match_type: Option<UriMatchType>) -> bool {
+        match match_type.unwrap_or(UriMatchType::Domain) {
+            UriMatchType::Domain => parse(login_uri)
+                .as_ref()
+                .and_then(domain)
+                .is_some_and(|domain| self.domains.contains(&domain)),
+            UriMatchType::Host => {
+                self.host.is_some() && parse(login_uri).as_ref().and_then(host) == self.host
+            }
+            UriMatchType::StartsWith => self.uri.starts_with(login_uri),
+            UriMatchType::Exact => self.uri == login_uri,
+            UriMatchType::RegularExpression => RegexBuilder::new(login_uri)
+                .case_insensitive(true)
+                .build()
+                .is_ok_and(|regex| regex.is_match(&self.uri)),
+            UriMatchType::Never => false,
+        }
+    }
+}
+
+/// Login URIs are often saved without a scheme, in which case they are treated as HTTP.
+#[must_use]
+pub fn parse(uri: &str) -> Option<Url> {
+    let uri = uri.trim();
+    if uri.contains("://") {
+        Url::parse(uri).ok()
+    } else if uri.contains('.') {
+        Url::parse(&format!("http://{uri}")).ok()
+    } else {
+        None
+    }
+}
+
+/// The host with its port, if one is given explicitly.
+fn host(url: &Url) -> Option<String> {
+    let host = url.host_str()?;
+    Some(match url.port() {
+        Some(port) => format!("{host}:{port}"),
+        None => host.to_owned(),
+    })
+}
+
+/// The registrable domain of the host, or the host itself if it is an IP address or has no
+/// registrable domain, like `localhost`.
+fn domain(url: &Url) -> Option<String> {
+    let host = match url.host()? {
+        url::Host::Domain(domain) => domain,
+        url::Host::Ipv4(_) | url::Host::Ipv6(_) => return url.host_str().map(str::to_owned),
+    };
+    Some(domains::registrable(host).unwrap_or(host).to_owned())
+}
+
+#[test]
+fn match_types() {
+    let page = Page::new(
+        "https://accounts.example.co.uk:8443/login?next=/".to_owned(),
+        &Domains::default(),
+    );
+    let matches = |uri, match_type| page.matches(uri, Some(match_type));
+
+    assert!(page.matches("example.co.uk", None));
+    assert!(matches("https://www.example.co.uk/", UriMatchType::Domain));
+    assert!(!matches("https://other.co.uk/", UriMatchType::Domain));
+
+    assert!(matches("accounts.example.co.uk:8443", UriMatchType::Host));
+    assert!(!matches(
+        "https://accounts.example.co.uk/",
+        UriMatchType::Host
+    ));
+
+    assert!(matches(
+        "https://accounts.example.co.uk:8443/login",
+        UriMatchType::StartsWith
+    ));
+    assert!(!matches(
+        "https://accounts.example.co.uk:8443/logout",
+        UriMatchType::StartsWith
+    ));
+
+    assert!(matches(
+        "https://accounts.example.co.uk:8443/login?next=/",
+        UriMatchType::Exact
+    ));
+    assert!(!matches(
+        "https://accounts.example.co.uk:8443/login",
+        UriMatchType::Exact
+    ));
+
+    assert!(matches(
+        r"^https://ACCOUNTS\.example\.co\.uk",
+        UriMatchType::RegularExpression
+    ));
+    assert!(!matches("(", UriMatchType::RegularExpression));
+
+    assert!(!matches(
+        "https://accounts.example.co.uk:8443/",
+        UriMatchType::Never
+    ));
+}
+
+#[test]
+fn equivalent_domains() {
+    let domains: Domains =
+        serde_json::from_str(r#"{"equivalentDomains": [["google.com", "youtube.com"]]}"#).unwrap();
+    let page = Page::new("https://www.youtube.com/".to_owned(), &domains);
+    assert!(page.matches("accounts.google.com", None));
+    assert!(!page.matches("accounts.google.com", Some(UriMatchType::Host)));
+}
+
+use crate::domains;
+use crate::domains::Domains;
+use regex::RegexBuilder;
+use serde::de;
+use serde::Deserialize;
+use serde::Deserializer;
+use std::fmt;
+use std::fmt::Formatter;
+use url::Url;
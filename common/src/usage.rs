@@ -0,0 +1,152 @@
+//! How often and how recently each item has been copied from, used to rank items by frecency.
+
+/// How lists of items are ordered.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, bincode::Encode, bincode::Decode,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Sort {
+    #[default]
+    Alphabetical,
+    /// Items used often and recently come first, followed by the rest in alphabetical order.
+    Frecency,
+}
+
+/// Identifies an item without revealing its UUID to anyone who doesn’t have the vault’s key, so
+/// that the record can be stored unencrypted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, bincode::Encode, bincode::Decode)]
+pub struct ItemKey([u8; 32]);
+
+impl ItemKey {
+    #[must_use]
+    pub fn new(key: &SymmetricKey, id: [u8; 16]) -> Self {
+        let mut mac = <Hmac<Sha256>>::new_from_slice(key.mac_key()).unwrap();
+        mac.update(&id);
+        Self(mac.finalize().into_bytes().into())
+    }
+}
+
+#[derive(Debug, Clone, Copy, bincode::Encode, bincode::Decode)]
+pub struct Usage {
+    pub count: u32,
+    /// In seconds since the Unix epoch.
+    pub last_used: u64,
+}
+
+impl Usage {
+    /// The use count weighted by how long ago the item was last used, following the buckets
+    /// Firefox uses for its address bar.
+    #[must_use]
+    pub fn frecency(&self, now: u64) -> u64 {
+        const DAY: u64 = 24 * 60 * 60;
+        let weight = match now.saturating_sub(self.last_used) / DAY {
+            0..=3 => 100,
+            4..=13 => 70,
+            14..=30 => 50,
+            31..=89 => 30,
+            _ => 10,
+        };
+        u64::from(self.count) * weight
+    }
+}
+
+#[derive(Debug, Default, Clone, bincode::Encode, bincode::Decode)]
+pub struct Record {
+    items: HashMap<ItemKey, Usage>,
+    /// The items most recently copied from or opened, most recent first.
+    recent: Vec<ItemKey>,
+}
+
+impl Record {
+    /// Entries unused for this long are forgotten, so items that have been deleted don’t stay in
+    /// the record forever.
+    const FORGET_AFTER: u64 = 365 * 24 * 60 * 60;
+
+    /// How many items the recently used list holds.
+    const RECENT_LEN: usize = 20;
+
+    /// Record that a value was copied from the item.
+    pub fn record_use(&mut self, item: ItemKey, now: u64) {
+        self.record_open(item);
+        self.items
+            .retain(|_, usage| now.saturating_sub(usage.last_used) < Self::FORGET_AFTER);
+        let usage = self.items.entry(item).or_insert(Usage {
+            count: 0,
+            last_used: now,
+        });
+        usage.count = usage.count.saturating_add(1);
+        usage.last_used = now;
+    }
+
+    /// Record that the item was opened, which only affects the recently used list.
+    pub fn record_open(&mut self, item: ItemKey) {
+        self.recent.retain(|&recent| recent != item);
+        self.recent.insert(0, item);
+        self.recent.truncate(Self::RECENT_LEN);
+    }
+
+    #[must_use]
+    pub fn get(&self, item: &ItemKey) -> Option<&Usage> {
+        self.items.get(item)
+    }
+
+    /// The items most recently copied from or opened, most recent first.
+    #[must_use]
+    pub fn recent(&self) -> &[ItemKey] {
+        &self.recent
+    }
+}
+
+/// The current time in seconds since the Unix epoch.
+#[must_use]
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+}
+
+#[test]
+fn frecency() {
+    const DAY: u64 = 24 * 60 * 60;
+    let now = 1000 * DAY;
+    let key = SymmetricKey::zeroed();
+    let (a, b) = (ItemKey::new(&key, [1; 16]), ItemKey::new(&key, [2; 16]));
+    assert_ne!(a, b);
+
+    let mut record = Record::default();
+    for _ in 0..5 {
+        record.record_use(a, now - 60 * DAY);
+    }
+    record.record_use(b, now - DAY);
+    record.record_use(b, now);
+
+    let score = |item| record.get(&item).unwrap().frecency(now);
+    assert_eq!(score(a), 150);
+    assert_eq!(score(b), 200);
+
+    record.record_use(b, now + 400 * DAY);
+    assert!(record.get(&a).is_none());
+}
+
+#[test]
+fn recent() {
+    let key = SymmetricKey::zeroed();
+    let item = |n| ItemKey::new(&key, [n; 16]);
+
+    let mut record = Record::default();
+    for n in 0..30 {
+        record.record_open(item(n));
+    }
+    record.record_use(item(25), 0);
+    assert_eq!(record.recent().len(), 20);
+    assert_eq!(record.recent()[..3], [item(25), item(29), item(28)]);
+    assert!(record.get(&item(29)).is_none());
+}
+
+use crate::SymmetricKey;
+use hmac::Hmac;
+use hmac::Mac as _;
+use serde::Deserialize;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::time::SystemTime;
@@ -2,8 +2,62 @@
 #[serde(rename_all = "camelCase")]
 pub(crate) struct Data {
     pub(crate) profile: Profile,
-    pub(crate) folders: Vec<Folder>,
-    pub(crate) ciphers: Vec<Cipher>,
+    pub(crate) folders: Vec<Lenient<Folder>>,
+    pub(crate) ciphers: Vec<Lenient<Cipher>>,
+    pub(crate) sends: Vec<Send>,
+    /// Missing from data synced by older versions of rofi-bw.
+    #[serde(default)]
+    pub(crate) domains: Option<Domains>,
+}
+
+/// An item that is parsed on its own, so that one this version doesn’t understand is shown as an
+/// error instead of stopping the whole vault from being shown.
+#[derive(Debug)]
+pub(crate) enum Lenient<T> {
+    Parsed(T),
+    Unparseable {
+        header: Header,
+        error: serde_json::Error,
+    },
+}
+
+impl<'de, T: DeserializeOwned> Deserialize<'de> for Lenient<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        Ok(match T::deserialize(&value) {
+            Ok(item) => Self::Parsed(item),
+            Err(error) => Self::Unparseable {
+                header: Header::deserialize(&value).unwrap_or_default(),
+                error,
+            },
+        })
+    }
+}
+
+/// The fields of an item needed to show a placeholder for it, read from items that can’t be
+/// parsed in full.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct Header {
+    pub(crate) id: Option<Uuid>,
+    pub(crate) folder_id: Option<Uuid>,
+    pub(crate) r#type: Option<u8>,
+    pub(crate) deleted_date: Option<String>,
+    pub(crate) favorite: bool,
+}
+
+impl Header {
+    /// The type of the cipher, or `None` if it is one we don’t support yet.
+    pub(crate) fn cipher_type(&self) -> Option<CipherType> {
+        Some(match self.r#type? {
+            1 => CipherType::Login,
+            2 => CipherType::SecureNote,
+            3 => CipherType::Card,
+            4 => CipherType::Identity,
+            5 => CipherType::SshKey,
+            _ => return None,
+        })
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -45,6 +99,8 @@ pub(crate) struct Cipher {
     pub(crate) data: CipherData,
     pub(crate) notes: Option<CipherString<String>>,
     pub(crate) fields: Option<Vec<Field>>,
+    pub(crate) password_history: Option<Vec<PasswordHistoryEntry>>,
+    pub(crate) attachments: Option<Vec<Attachment>>,
 }
 
 impl<'de> Deserialize<'de> for Cipher {
@@ -64,13 +120,16 @@ impl<'de> Deserialize<'de> for Cipher {
             favorite: bool,
             notes: Option<CipherString<String>>,
             fields: Option<Vec<Field>>,
+            password_history: Option<Vec<PasswordHistoryEntry>>,
+            attachments: Option<Vec<Attachment>>,
 
-            r#type: u32,
+            // r#type: u32,
             login: Option<Login>,
             // this doesn’t contain anything interesting
             secure_note: Option<de::IgnoredAny>,
             card: Option<Card>,
             identity: Option<Identity>,
+            ssh_key: Option<SshKey>,
         }
         let inner = Inner::deserialize(deserializer)?;
         Ok(Self {
@@ -84,14 +143,15 @@ impl<'de> Deserialize<'de> for Cipher {
             favourite: inner.favorite,
             notes: inner.notes,
             fields: inner.fields,
+            password_history: inner.password_history,
+            attachments: inner.attachments,
             data: None
                 .or_else(|| inner.login.map(CipherData::Login))
                 .or_else(|| inner.secure_note.map(|_| CipherData::SecureNote))
                 .or_else(|| inner.card.map(CipherData::Card))
                 .or_else(|| inner.identity.map(CipherData::Identity))
-                .ok_or_else(|| {
-                    de::Error::custom(format_args!("unknown card type {}", inner.r#type))
-                })?,
+                .or_else(|| inner.ssh_key.map(CipherData::SshKey))
+                .unwrap_or(CipherData::Unknown),
         })
     }
 }
@@ -104,6 +164,9 @@ pub(crate) enum CipherData {
     SecureNote,
     Card(Card),
     Identity(Identity),
+    SshKey(SshKey),
+    /// A type of cipher that we don’t support yet; these are skipped.
+    Unknown,
 }
 
 #[derive(Debug, Deserialize)]
@@ -113,8 +176,38 @@ pub(crate) struct Login {
     pub(crate) uris: Option<Vec<Uri>>,
     pub(crate) username: Option<CipherString<String>>,
     pub(crate) password: Option<CipherString<String>>,
+    pub(crate) totp: Option<CipherString<String>>,
     // #[serde(with = "time::serde::rfc3339::option")]
     // pub(crate) password_revision_date: Option<OffsetDateTime>,
+    pub(crate) fido2_credentials: Option<Vec<Fido2Credential>>,
+}
+
+/// A passkey. Only its metadata is used; the key itself is never decrypted.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Fido2Credential {
+    // pub(crate) credential_id: CipherString<String>,
+    // pub(crate) key_type: CipherString<String>,
+    // pub(crate) key_algorithm: CipherString<String>,
+    // pub(crate) key_curve: CipherString<String>,
+    // pub(crate) key_value: CipherString<String>,
+    pub(crate) rp_id: CipherString<String>,
+    // pub(crate) rp_name: Option<CipherString<String>>,
+    // pub(crate) user_handle: Option<CipherString<String>>,
+    pub(crate) user_name: Option<CipherString<String>>,
+    // pub(crate) user_display_name: Option<CipherString<String>>,
+    // pub(crate) counter: CipherString<String>,
+    // pub(crate) discoverable: CipherString<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub(crate) creation_date: OffsetDateTime,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SshKey {
+    pub(crate) private_key: CipherString<String>,
+    pub(crate) public_key: CipherString<String>,
+    pub(crate) key_fingerprint: CipherString<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -155,43 +248,28 @@ pub(crate) struct Identity {
 pub(crate) struct Uri {
     pub(crate) uri: CipherString<String>,
     #[serde(rename = "match")]
-    // Dead code, but there’s enough of it that I want to make sure it always compiles
-    #[allow(dead_code)]
     pub(crate) match_type: Option<UriMatchType>,
 }
 
-#[derive(Debug)]
-pub(crate) enum UriMatchType {
-    Domain = 0,
-    Host = 1,
-    StartsWith = 2,
-    Exact = 3,
-    RegularExpression = 4,
-    Never = 5,
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PasswordHistoryEntry {
+    pub(crate) password: CipherString<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub(crate) last_used_date: OffsetDateTime,
 }
 
-impl<'de> Deserialize<'de> for UriMatchType {
-    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
-        struct Visitor;
-        impl<'de> de::Visitor<'de> for Visitor {
-            type Value = UriMatchType;
-            fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
-                f.write_str("a URI match type")
-            }
-            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
-                Ok(match v {
-                    0 => UriMatchType::Domain,
-                    1 => UriMatchType::Host,
-                    2 => UriMatchType::StartsWith,
-                    3 => UriMatchType::Exact,
-                    4 => UriMatchType::RegularExpression,
-                    5 => UriMatchType::Never,
-                    _ => return Err(de::Error::invalid_value(de::Unexpected::Unsigned(v), &self)),
-                })
-            }
-        }
-        deserializer.deserialize_u64(Visitor)
-    }
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Attachment {
+    pub(crate) id: String,
+    // pub(crate) url: String,
+    pub(crate) file_name: CipherString<String>,
+    /// The attachment’s own key, encrypted with the user key. Very old attachments don’t have one
+    /// and are encrypted with the user key directly.
+    pub(crate) key: Option<CipherString<Vec<u8>>>,
+    // pub(crate) size: String,
+    pub(crate) size_name: String,
 }
 
 #[derive(Debug)]
@@ -401,10 +479,56 @@ impl<'de> Deserialize<'de> for Linked {
     }
 }
 
-use crate::cipher_string;
-use crate::symmetric_key::SymmetricKey;
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Send {
+    pub(crate) id: Uuid,
+    pub(crate) access_id: String,
+    // pub(crate) r#type: u8,
+    /// The key material of the Send, encrypted with the user key. The other encrypted fields are
+    /// encrypted with the key derived from it.
+    pub(crate) key: CipherString<Vec<u8>>,
+    pub(crate) name: CipherString<String>,
+    pub(crate) notes: Option<CipherString<String>>,
+    pub(crate) text: Option<SendText>,
+    pub(crate) file: Option<SendFile>,
+    pub(crate) max_access_count: Option<u32>,
+    pub(crate) access_count: u32,
+    // pub(crate) password: Option<String>,
+    pub(crate) disabled: bool,
+    // pub(crate) hide_email: bool,
+    // #[serde(with = "time::serde::rfc3339")]
+    // pub(crate) revision_date: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub(crate) expiration_date: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub(crate) deletion_date: OffsetDateTime,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SendText {
+    pub(crate) text: Option<CipherString<String>>,
+    pub(crate) hidden: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SendFile {
+    // pub(crate) id: String,
+    pub(crate) file_name: CipherString<String>,
+    // pub(crate) size: String,
+    pub(crate) size_name: String,
+}
+
 use crate::CipherString;
+use crate::SymmetricKey;
+use rofi_bw_common::cipher_string;
+use rofi_bw_common::domains::Domains;
+use rofi_bw_common::uri_match::UriMatchType;
+use rofi_bw_common::CipherType;
 use serde::de;
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
 use serde::Deserializer;
 use std::fmt;
@@ -119,12 +119,15 @@ pub(crate) enum Glyph {
     SecureNote,
     Card,
     Identity,
+    SshKey,
 
     Folder,
 
+    Ban,
     Briefcase,
     Chain,
     Clock,
+    Error,
     EyeSlash,
     Hash,
     Key,
@@ -132,14 +135,17 @@ pub(crate) enum Glyph {
     Mail,
     Mobile,
     Padlock,
+    Paperclip,
+    Passkey,
     Pencil,
     Square,
     SquareCheck,
+    Trash,
     User,
 }
 
 impl Glyph {
-    const COUNT: usize = 19;
+    const COUNT: usize = 25;
 
     fn to_char(self) -> u16 {
         // See:
@@ -149,12 +155,15 @@ impl Glyph {
             Self::SecureNote => 0xE90A,
             Self::Card => 0xE908,
             Self::Identity => 0xE907,
+            Self::SshKey => 0xE987,
 
             Self::Folder => 0xE90B,
 
+            Self::Ban => 0xE967,
             Self::Briefcase => 0xE98C,
             Self::Chain => 0xE954,
             Self::Clock => 0xE92C,
+            Self::Error => 0xE94A,
             Self::EyeSlash => 0xE96D,
             Self::Hash => 0xE904,
             Self::Key => 0xE902,
@@ -162,9 +171,13 @@ impl Glyph {
             Self::Mail => 0xE949,
             Self::Mobile => 0xE986,
             Self::Padlock => 0xE90C,
+            Self::Paperclip => 0xE93F,
+            // The font has no passkey icon, so a shield stands in for one.
+            Self::Passkey => 0xE932,
             Self::Pencil => 0xE929,
             Self::Square => 0xE92F,
             Self::SquareCheck => 0xE93B,
+            Self::Trash => 0xE917,
             Self::User => 0xE900,
         }
     }
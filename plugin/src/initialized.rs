@@ -1,19 +1,110 @@
 pub(crate) struct Initialized {
     state: State,
     icons: Icons,
-    // Currently unused, but may be useful in future
+    /// Shown at the bottom of the status line, e.g. to report items that failed to decrypt.
     error_message: String,
+    /// When the vault will lock for having been unlocked for too long.
+    lock_at: Option<Instant>,
+    /// The items opened since the menu was shown, to be added to the recently used list.
+    opened: Vec<typed_slice::Index<Cipher>>,
+    /// An action waiting for its key to be pressed again to confirm it.
+    pending: Option<Pending>,
 }
 
+/// An action that can’t be undone or is easily triggered by mistake, so it must be confirmed by
+/// pressing its key a second time.
+#[derive(PartialEq)]
+enum Pending {
+    CreateFolder(String),
+    DeleteFolder { id: [u8; 16], name: String },
+    DeleteSend { id: [u8; 16], name: String },
+}
+
+impl Pending {
+    fn question(&self) -> String {
+        match self {
+            Self::CreateFolder(name) => format!("Create folder “{name}”?"),
+            Self::DeleteFolder { name, .. } => format!("Delete folder “{name}”?"),
+            Self::DeleteSend { name, .. } => format!("Delete Send “{name}”?"),
+        }
+    }
+
+    fn keybind(&self) -> &'static Keybind<RofiAction> {
+        let action = match self {
+            Self::CreateFolder(_) => RofiAction::CreateFolder,
+            Self::DeleteFolder { .. } => RofiAction::DeleteFolder,
+            Self::DeleteSend { .. } => RofiAction::DeleteSend,
+        };
+        ROFI_KEYBINDS.iter().find(|k| k.action == action).unwrap()
+    }
+}
+
+/// What rofi’s own keybinds do in the lists they are shown in.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum RofiAction {
+    CreateFolder,
+    DeleteFolder,
+    CopyLink,
+    ShowSend,
+    DeleteSend,
+}
+
+impl RofiAction {
+    /// The list whose help text the keybind is shown in.
+    fn list(self) -> List {
+        match self {
+            Self::CreateFolder | Self::DeleteFolder => List::Folders,
+            Self::CopyLink | Self::ShowSend | Self::DeleteSend => List::Sends,
+        }
+    }
+}
+
+/// Rofi’s built-in keybinds for the actions they trigger in this mode.
+const ROFI_KEYBINDS: &[Keybind<RofiAction>] = &[
+    Keybind {
+        combination: Cow::Borrowed("Control+Enter"),
+        action: RofiAction::CreateFolder,
+        description: "Create folder named input",
+    },
+    Keybind {
+        combination: Cow::Borrowed("Shift+Delete"),
+        action: RofiAction::DeleteFolder,
+        description: "Delete folder",
+    },
+    Keybind {
+        combination: Cow::Borrowed("Enter"),
+        action: RofiAction::CopyLink,
+        description: "Copy link",
+    },
+    Keybind {
+        combination: Cow::Borrowed("Shift+Enter"),
+        action: RofiAction::ShowSend,
+        description: "Show Send",
+    },
+    Keybind {
+        combination: Cow::Borrowed("Shift+Delete"),
+        action: RofiAction::DeleteSend,
+        description: "Delete Send",
+    },
+];
+
 impl Initialized {
     pub(crate) fn new(
         master_key: &MasterKey,
         data: Data,
         history: History<ipc::View>,
+        lock_at: Option<Instant>,
+        options: &Options<'_>,
     ) -> anyhow::Result<Self> {
         let mut icons = Icons::new()?;
 
-        let state = State::new(master_key, data, history)?;
+        let (state, errors) = State::new(master_key, data, history, options)?;
+
+        let error_message = match errors {
+            0 => String::new(),
+            1 => "<span foreground='red'>Error:</span> 1 item could not be decrypted".to_owned(),
+            n => format!("<span foreground='red'>Error:</span> {n} items could not be decrypted"),
+        };
 
         for cipher in &*state.ciphers {
             icons.start_fetch(&cipher.icon);
@@ -22,22 +113,70 @@ impl Initialized {
         Ok(Self {
             state,
             icons,
-            error_message: String::new(),
+            error_message,
+            lock_at,
+            opened: Vec::new(),
+            pending: None,
         })
     }
 }
 
+/// How the vault is processed, as configured by the user.
+pub(crate) struct Options<'a> {
+    pub(crate) search_fields: ipc::SearchFields,
+    pub(crate) usage: &'a usage::Record,
+    pub(crate) sort: Sort,
+    pub(crate) default_copy: &'a DefaultCopy,
+}
+
 impl Initialized {
     pub(crate) const DISPLAY_NAME: &'static str = "bitwarden";
 
     pub(crate) fn status(&self, s: &mut rofi_mode::String) {
-        s.push_str(match *self.state.history.current() {
-            View::List(list) => list.description(),
-            View::Folder(i) => &self.state.folders[i].name,
-            View::Cipher(i) => &self.state.ciphers[i].name,
-        });
+        match *self.state.history.current() {
+            View::List(list) => s.push_str(list.description()),
+            View::Folder(i) => s.push_str(&self.state.folders[i].name),
+            View::Cipher(i) => s.push_str(&self.state.ciphers[i].name),
+            View::PasswordHistory(i) => {
+                s.push_str(&self.state.ciphers[i].name);
+                s.push_str(": password history");
+            }
+            View::Send(i) => {
+                s.push_str("Send: ");
+                s.push_str(&self.state.sends[i].name);
+            }
+            View::Uri(i) => {
+                s.push_str("Logins for ");
+                s.push_str(self.state.uri_views[i].page.uri());
+            }
+        }
         s.push_str("\n");
 
+        let list = match self.state.viewing() {
+            Viewing::Folders(_) => Some(List::Folders),
+            Viewing::Sends(_) => Some(List::Sends),
+            _ => None,
+        };
+        if let Some(list) = list {
+            let row = (ROFI_KEYBINDS.iter())
+                .filter(|keybind| keybind.action.list() == list)
+                .cloned()
+                .collect::<Vec<_>>();
+            writeln!(s, "{}", keybind::HelpMarkup(&row)).unwrap();
+        }
+
+        if let Some(pending) = &self.pending {
+            let question = glib::markup_escape_text(&pending.question());
+            let confirm = pending.keybind().combination.as_ref();
+            writeln!(s, "{question} <b>{confirm}</b>: Confirm").unwrap();
+        }
+
+        if let Some(lock_at) = self.lock_at {
+            let minutes = lock_at.saturating_duration_since(Instant::now()).as_secs() / 60;
+            let (hours, minutes) = (minutes / 60, minutes % 60);
+            s.push_str(&format!("Vault locks in {hours}h {minutes}m\n"));
+        }
+
         if !self.error_message.is_empty() {
             s.push_str(&self.error_message);
         }
@@ -48,6 +187,9 @@ impl Initialized {
             Viewing::CipherList(list) => list.len(),
             Viewing::Folders(folders) => folders.len(),
             Viewing::Cipher(cipher) => cipher.fields.len(),
+            Viewing::PasswordHistory(cipher) => cipher.password_history.len(),
+            Viewing::Sends(sends) => sends.len(),
+            Viewing::Send(send) => send.fields.len(),
         }
     }
 
@@ -56,6 +198,18 @@ impl Initialized {
             Viewing::CipherList(list) => &self.state.ciphers[list[line]].name,
             Viewing::Folders(folders) => &folders[typed_slice::Index::from_raw(line)].name,
             Viewing::Cipher(cipher) => &cipher.fields[line].display,
+            Viewing::PasswordHistory(cipher) => &cipher.password_history[line].display,
+            Viewing::Sends(sends) => &sends[typed_slice::Index::from_raw(line)].display,
+            Viewing::Send(send) => &send.fields[line].display,
+        }
+    }
+
+    /// The text the filter is matched against, which for items also includes the fields chosen in
+    /// the config.
+    pub(crate) fn entry_search_text(&self, line: usize) -> &str {
+        match self.state.viewing() {
+            Viewing::CipherList(list) => &self.state.ciphers[list[line]].search,
+            _ => self.entry_content(line),
         }
     }
 
@@ -64,6 +218,9 @@ impl Initialized {
             Viewing::CipherList(list) => &self.state.ciphers[list[line]].icon,
             Viewing::Folders(_) => &Icon::Glyph(icons::Glyph::Folder),
             Viewing::Cipher(cipher) => &cipher.fields[line].icon,
+            Viewing::PasswordHistory(cipher) => &cipher.password_history[line].icon,
+            Viewing::Sends(sends) => &sends[typed_slice::Index::from_raw(line)].icon,
+            Viewing::Send(send) => &send.fields[line].icon,
         };
         self.icons.surface(icon, height)
     }
@@ -75,12 +232,15 @@ impl Initialized {
     pub(crate) fn parent(&mut self) {
         let parent = match *self.state.history.current() {
             View::List(List::Trash) => View::List(List::Trash),
-            View::List(List::All | List::Favourites | List::TypeBucket(_)) => View::List(List::All),
+            View::List(List::All | List::Recent | List::Favourites | List::TypeBucket(_))
+            | View::Uri(_) => View::List(List::All),
             View::List(List::Folders) | View::Folder(_) => View::List(List::Folders),
             View::Cipher(i) => {
                 let folder_id = self.state.ciphers[i].folder_id;
                 View::Folder(self.state.folder_map[&folder_id])
             }
+            View::PasswordHistory(i) => View::Cipher(i),
+            View::List(List::Sends) | View::Send(_) => View::List(List::Sends),
         };
         self.state.history.push(parent);
     }
@@ -96,7 +256,7 @@ impl Initialized {
         match self.state.viewing() {
             Viewing::CipherList(list) => {
                 input.clear();
-                self.state.history.push(View::Cipher(list[line]));
+                self.open_cipher(list[line]);
             }
             Viewing::Folders(_) => {
                 input.clear();
@@ -104,10 +264,37 @@ impl Initialized {
                     .history
                     .push(View::Folder(typed_slice::Index::from_raw(line)));
             }
-            Viewing::Cipher(_) => {}
+            Viewing::Sends(_) => {
+                input.clear();
+                self.state
+                    .history
+                    .push(View::Send(typed_slice::Index::from_raw(line)));
+            }
+            Viewing::Cipher(_) | Viewing::PasswordHistory(_) | Viewing::Send(_) => {}
         }
     }
 
+    /// Like [`Self::ok`], but copying to the selection other than the configured one.
+    pub(crate) fn ok_other_selection(
+        &mut self,
+        line: usize,
+        input: &mut rofi_mode::String,
+    ) -> Option<ipc::MenuRequest> {
+        let mut request = self.ok(line, input)?;
+        if let ipc::MenuRequest::Copy {
+            other_selection, ..
+        } = &mut request
+        {
+            *other_selection = true;
+        }
+        Some(request)
+    }
+
+    fn open_cipher(&mut self, i: typed_slice::Index<Cipher>) {
+        self.state.history.push(View::Cipher(i));
+        self.opened.push(i);
+    }
+
     pub(crate) fn ok(
         &mut self,
         line: usize,
@@ -117,10 +304,10 @@ impl Initialized {
             Viewing::CipherList(list) => {
                 let cipher = &self.state.ciphers[list[line]];
                 match cipher.default_copy {
-                    Some(default_copy) => (cipher, default_copy),
+                    Some(default_copy) => (cipher, &cipher.fields[default_copy]),
                     None => {
                         input.clear();
-                        self.state.history.push(View::Cipher(list[line]));
+                        self.open_cipher(list[line]);
                         return None;
                     }
                 }
@@ -132,32 +319,33 @@ impl Initialized {
                     .push(View::Folder(typed_slice::Index::from_raw(line)));
                 return None;
             }
-            Viewing::Cipher(cipher) => (cipher, line),
+            Viewing::Cipher(cipher) => (cipher, &cipher.fields[line]),
+            Viewing::PasswordHistory(cipher) => (cipher, &cipher.password_history[line]),
+            Viewing::Sends(sends) => {
+                let send = &sends[typed_slice::Index::from_raw(line)];
+                return self.ok_send(send, &send.fields[Send::LINK_FIELD], input);
+            }
+            Viewing::Send(send) => return self.ok_send(send, &send.fields[line], input),
         };
 
-        let field = &cipher.fields[field];
-
         match field.action.as_ref()? {
             Action::Copy { name, data, hidden } => {
-                let cipher_name = cipher.name.clone();
-                let reprompt = *hidden && cipher.reprompt;
-
-                let image_path = self
-                    .icons
-                    .fs_path(&cipher.icon)
-                    .and_then(|path| std::fs::canonicalize(path).ok())
-                    .and_then(|path| path.into_os_string().into_string().ok());
-
-                Some(ipc::MenuRequest::Copy {
-                    cipher_name,
+                let menu_state = self.menu_state(input.to_string());
+                let step = CopyStep {
                     field: name.clone().into_owned(),
-                    data: data.to_string(),
-                    image_path,
-                    reprompt,
-                    menu_state: ipc::menu_request::MenuState {
-                        filter: input.to_string(),
-                        history: self.ipc_state(),
-                    },
+                    data: data.clone(),
+                };
+                Some(cipher.copy_request(&mut self.icons, step, Vec::new(), *hidden, menu_state))
+            }
+            Action::SaveAttachment { id, file_name, key } => {
+                Some(ipc::MenuRequest::SaveAttachment {
+                    cipher_name: cipher.name.clone(),
+                    cipher_id: cipher.id.into_bytes(),
+                    attachment_id: id.clone(),
+                    file_name: file_name.clone(),
+                    key: key.clone(),
+                    reprompt: cipher.reprompt,
+                    menu_state: self.menu_state(input.to_string()),
                 })
             }
             Action::Link { to } => {
@@ -165,14 +353,286 @@ impl Initialized {
                 input.push_str(to);
                 None
             }
+            Action::ShowPasswordHistory => {
+                if let View::Cipher(i) = *self.state.history.current() {
+                    input.clear();
+                    self.state.history.push(View::PasswordHistory(i));
+                }
+                None
+            }
+            Action::EditSend(_) => None,
         }
     }
 
+    /// Copy a field of the item on the given line of a list of items, without opening it.
+    pub(crate) fn quick_copy(
+        &mut self,
+        line: usize,
+        field: QuickCopy,
+        input: &mut rofi_mode::String,
+    ) -> Option<ipc::MenuRequest> {
+        let Viewing::CipherList(list) = self.state.viewing() else {
+            return None;
+        };
+        let cipher = &self.state.ciphers[list[line]];
+
+        let copy_field = |field_name: &str| {
+            cipher.fields.iter().find_map(|field| match &field.action {
+                Some(Action::Copy { name, data, hidden }) if name == field_name => {
+                    let step = CopyStep {
+                        field: field_name.to_owned(),
+                        data: data.clone(),
+                    };
+                    Some((step, *hidden))
+                }
+                _ => None,
+            })
+        };
+
+        let single = |field_name| {
+            let (step, hidden) = copy_field(field_name)?;
+            Some((step, Vec::new(), hidden))
+        };
+
+        let (step, then, hidden) = match field {
+            QuickCopy::Username => single("username")?,
+            QuickCopy::Password => single("password")?,
+            QuickCopy::Uri => single("URI")?,
+            QuickCopy::Sequence => {
+                let mut steps = ["username", "password"].into_iter().filter_map(copy_field);
+                let (first, mut hidden) = steps.next()?;
+                let then = (steps.map(|(step, step_hidden)| {
+                    hidden |= step_hidden;
+                    step
+                }))
+                .collect();
+                (first, then, hidden)
+            }
+            QuickCopy::Totp => {
+                let code = match totp::generate(cipher.totp.as_ref()?, SystemTime::now()) {
+                    Ok(code) => code,
+                    Err(e) => {
+                        self.error_message = error_status(e.context("failed to generate TOTP"));
+                        return None;
+                    }
+                };
+                let step = CopyStep {
+                    field: "TOTP code".to_owned(),
+                    data: code,
+                };
+                (step, Vec::new(), true)
+            }
+        };
+
+        let menu_state = self.menu_state(input.to_string());
+        Some(cipher.copy_request(&mut self.icons, step, then, hidden, menu_state))
+    }
+
+    fn ok_send(
+        &self,
+        send: &Send,
+        field: &Field,
+        input: &mut rofi_mode::String,
+    ) -> Option<ipc::MenuRequest> {
+        match field.action.as_ref()? {
+            Action::Copy { name, data, hidden } => Some(ipc::MenuRequest::Copy {
+                cipher_name: send.name.clone(),
+                cipher_id: None,
+                field: name.clone().into_owned(),
+                data: data.clone(),
+                then: Vec::new(),
+                secret: *hidden,
+                other_selection: false,
+                image_path: None,
+                reprompt: false,
+                menu_state: self.menu_state(input.to_string()),
+            }),
+            Action::EditSend(edit) => Some(self.edit_send(edit.clone(), input)),
+            Action::Link { .. } | Action::ShowPasswordHistory | Action::SaveAttachment { .. } => {
+                None
+            }
+        }
+    }
+
+    pub(crate) fn create_folder(
+        &mut self,
+        input: &mut rofi_mode::String,
+    ) -> Option<ipc::MenuRequest> {
+        // Rofi also reports Enter with no matching entries as custom input, so a mistyped filter
+        // must not create a folder straight away.
+        if !self.viewing_folders() || !self.confirm(Pending::CreateFolder(input.trim().to_owned()))
+        {
+            return None;
+        }
+        let name = folder_name(input)?;
+        Some(self.edit_folder(FolderEdit::Create { name }, String::new(), input))
+    }
+
+    pub(crate) fn rename_folder(
+        &mut self,
+        line: usize,
+        input: &mut rofi_mode::String,
+    ) -> Option<ipc::MenuRequest> {
+        let id = self.folder_id(line)?;
+        let name = folder_name(input)?;
+        Some(self.edit_folder(FolderEdit::Rename { id, name }, String::new(), input))
+    }
+
+    /// Delete the folder or Send on the given line.
+    pub(crate) fn delete_entry(
+        &mut self,
+        line: usize,
+        input: &mut rofi_mode::String,
+    ) -> Option<ipc::MenuRequest> {
+        match self.state.viewing() {
+            Viewing::Sends(sends) => {
+                let send = &sends[typed_slice::Index::from_raw(line)];
+                let (id, name) = (send.id.into_bytes(), send.name.clone());
+                if !self.confirm(Pending::DeleteSend { id, name }) {
+                    return None;
+                }
+                Some(self.edit_send(SendEdit::Delete { id }, input))
+            }
+            _ => self.delete_folder(line, input),
+        }
+    }
+
+    fn edit_send(&self, edit: SendEdit, input: &mut rofi_mode::String) -> ipc::MenuRequest {
+        let mut menu_state = self.menu_state(input.to_string());
+        // A deleted Send can’t be shown any more, so go back to the list of Sends.
+        if let (SendEdit::Delete { .. }, View::Send(_)) = (&edit, self.state.history.current()) {
+            menu_state.history.push(ipc::View::List(List::Sends));
+        }
+        ipc::MenuRequest::EditSend { edit, menu_state }
+    }
+
+    fn delete_folder(
+        &mut self,
+        line: usize,
+        input: &mut rofi_mode::String,
+    ) -> Option<ipc::MenuRequest> {
+        let id = self.folder_id(line)?;
+        let name = self.state.folders[typed_slice::Index::from_raw(line)]
+            .name
+            .clone();
+        if !self.confirm(Pending::DeleteFolder { id, name }) {
+            return None;
+        }
+        let filter = input.to_string();
+        Some(self.edit_folder(FolderEdit::Delete { id }, filter, input))
+    }
+
+    /// Returns whether the action was already pending, in which case this confirms it. Otherwise
+    /// it becomes pending until it is confirmed or [`Self::cancel_pending`] is called.
+    fn confirm(&mut self, action: Pending) -> bool {
+        if self.pending.as_ref() == Some(&action) {
+            self.pending = None;
+            true
+        } else {
+            self.pending = Some(action);
+            false
+        }
+    }
+
+    pub(crate) fn cancel_pending(&mut self) {
+        self.pending = None;
+    }
+
+    /// Create a Send of the copyable field on the given line of a cipher, or otherwise of the
+    /// text in the input box.
+    pub(crate) fn create_send(
+        &mut self,
+        line: Option<usize>,
+        input: &mut rofi_mode::String,
+    ) -> Option<ipc::MenuRequest> {
+        let field = line.and_then(|line| match self.state.viewing() {
+            Viewing::Cipher(cipher) => Some((cipher, &cipher.fields[line])),
+            Viewing::PasswordHistory(cipher) => Some((cipher, &cipher.password_history[line])),
+            Viewing::CipherList(_) | Viewing::Folders(_) | Viewing::Sends(_) | Viewing::Send(_) => {
+                None
+            }
+        });
+
+        let (name, text, hidden, reprompt_cipher) = match field {
+            Some((
+                cipher,
+                Field {
+                    action: Some(Action::Copy { name, data, hidden }),
+                    ..
+                },
+            )) => (
+                format!("{} {name}", cipher.name),
+                data.clone(),
+                *hidden,
+                (*hidden && cipher.reprompt).then(|| cipher.name.clone()),
+            ),
+            _ if !input.is_empty() => (
+                send::DEFAULT_NAME.to_owned(),
+                input.to_string(),
+                false,
+                None,
+            ),
+            _ => return None,
+        };
+
+        Some(ipc::MenuRequest::CreateSend {
+            name,
+            text,
+            hidden,
+            reprompt_cipher,
+            menu_state: self.menu_state(input.to_string()),
+        })
+    }
+
+    /// Get the UUID of the folder on the given line of the folder list. Returns `None` if the
+    /// folder list is not being shown or if the line is the “No folder” folder, which cannot be
+    /// edited.
+    fn folder_id(&self, line: usize) -> Option<[u8; 16]> {
+        match self.state.viewing() {
+            Viewing::Folders(folders) => {
+                let folder = &folders[typed_slice::Index::from_raw(line)];
+                folder.id.map(Uuid::into_bytes)
+            }
+            _ => None,
+        }
+    }
+
+    fn edit_folder(
+        &self,
+        edit: FolderEdit,
+        filter: String,
+        input: &mut rofi_mode::String,
+    ) -> ipc::MenuRequest {
+        input.clear();
+        ipc::MenuRequest::EditFolder {
+            edit,
+            menu_state: self.menu_state(filter),
+        }
+    }
+
+    pub(crate) fn viewing_folders(&self) -> bool {
+        matches!(self.state.viewing(), Viewing::Folders(_))
+    }
+
+    pub(crate) fn viewing_items(&self) -> bool {
+        matches!(self.state.viewing(), Viewing::CipherList(_))
+    }
+
     pub(crate) fn history(&self) -> &History<impl PartialEq> {
         &self.state.history
     }
 
-    pub(crate) fn ipc_state(&self) -> History<ipc::View> {
+    pub(crate) fn menu_state(&self, filter: String) -> ipc::menu_request::MenuState {
+        ipc::menu_request::MenuState {
+            filter,
+            history: self.ipc_state(),
+            opened: (self.opened.iter())
+                .map(|&i| self.state.ciphers[i].id.into_bytes())
+                .collect(),
+        }
+    }
+
+    fn ipc_state(&self) -> History<ipc::View> {
         self.state.history.ref_map(|view| match *view {
             View::List(list) => ipc::View::List(list),
             View::Folder(i) => {
@@ -187,6 +647,12 @@ impl Initialized {
                 let uuid = self.state.ciphers[i].id;
                 ipc::View::Cipher(ipc::Filter::Uuid(uuid.into_bytes()))
             }
+            View::PasswordHistory(i) => {
+                let uuid = self.state.ciphers[i].id;
+                ipc::View::PasswordHistory(ipc::Filter::Uuid(uuid.into_bytes()))
+            }
+            View::Send(i) => ipc::View::Send(self.state.sends[i].id.into_bytes()),
+            View::Uri(i) => ipc::View::Uri(self.state.uri_views[i].page.uri().to_owned()),
         })
     }
 }
@@ -195,11 +661,42 @@ struct State {
     history: History<View>,
     ciphers: Box<TypedSlice<Cipher>>,
     all: Vec<typed_slice::Index<Cipher>>,
+    /// The items most recently copied from or opened, most recent first.
+    recent: Vec<typed_slice::Index<Cipher>>,
     trash: Vec<typed_slice::Index<Cipher>>,
     favourites: Vec<typed_slice::Index<Cipher>>,
     type_buckets: CipherTypeList<Vec<typed_slice::Index<Cipher>>>,
     folders: Box<TypedSlice<Folder>>,
     folder_map: FolderMap,
+    sends: Box<TypedSlice<Send>>,
+    /// The lists of logins matching the URIs in history.
+    uri_views: Vec<UriView>,
+}
+
+struct UriView {
+    page: uri_match::Page,
+    contents: Vec<typed_slice::Index<Cipher>>,
+}
+
+impl UriView {
+    fn new(
+        uri: String,
+        domains: &Domains,
+        ciphers: &TypedSlice<Cipher>,
+        all: &[typed_slice::Index<Cipher>],
+    ) -> Self {
+        let page = uri_match::Page::new(uri, domains);
+        let contents = all
+            .iter()
+            .copied()
+            .filter(|&i| {
+                let uris = &ciphers[i].login_uris;
+                uris.iter()
+                    .any(|login| page.matches(&login.uri, login.match_type))
+            })
+            .collect();
+        Self { page, contents }
+    }
 }
 
 type FolderMap = HashMap<Option<Uuid>, typed_slice::Index<Folder>>;
@@ -209,24 +706,40 @@ enum View {
     List(List),
     Folder(typed_slice::Index<Folder>),
     Cipher(typed_slice::Index<Cipher>),
+    PasswordHistory(typed_slice::Index<Cipher>),
+    Send(typed_slice::Index<Send>),
+    /// An index into `State::uri_views`.
+    Uri(usize),
 }
 
 impl State {
+    /// Process the vault, also returning the number of items that could not be decrypted.
+    ///
+    /// Items that fail to decrypt don’t cause an error; they are replaced with placeholders that
+    /// show what went wrong.
     pub(crate) fn new(
         master_key: &MasterKey,
         data: Data,
         history: History<ipc::View>,
-    ) -> anyhow::Result<Self> {
+        options: &Options<'_>,
+    ) -> anyhow::Result<(Self, usize)> {
         let key = data.profile.key.decrypt(master_key)?;
 
         let collator = Collator::default_locale()?;
 
         let (folders_result, ciphers_result) = rayon::join(
             || process_folders(data.folders, &key, &collator),
-            || process_ciphers(data.ciphers, &key, &collator),
+            || process_ciphers(data.ciphers, &key, &collator, options.search_fields),
         );
-        let (mut folders, folder_map) = folders_result?;
-        let ciphers = ciphers_result?;
+        let (mut folders, folder_map, folder_errors) = folders_result?;
+        let (mut ciphers, cipher_errors) = ciphers_result?;
+        let (sends, send_errors) = process_sends(data.sends, &key, &collator)?;
+
+        if options.search_fields.folder {
+            add_folders_to_search(&mut ciphers, &folders, &folder_map);
+        }
+
+        choose_default_copies(&mut ciphers, &folders, &folder_map, options.default_copy);
 
         let mut all = Vec::new();
         let mut trash = Vec::new();
@@ -246,39 +759,73 @@ impl State {
 
             type_buckets[cipher.r#type].push(i);
 
-            let &folder = folder_map.get(&cipher.folder_id).with_context(|| {
-                format!("Item {} is contained in non-existent folder", cipher.name)
-            })?;
+            // Items in folders that don’t exist are shown in “No folder” rather than being lost.
+            let folder = match folder_map.get(&cipher.folder_id) {
+                Some(&folder) => folder,
+                None => {
+                    eprintln!(
+                        "Warning: item {} is contained in non-existent folder",
+                        cipher.name
+                    );
+                    folders.last_index()
+                }
+            };
             folders[folder].contents.push(i);
         }
 
-        Ok(Self {
-            history: history.map(|view| match view {
-                ipc::View::List(list) => View::List(list),
-                ipc::View::NoFolder => View::Folder(folders.last_index()),
-                ipc::View::Folder(filter) => {
-                    let index = match filter {
-                        ipc::Filter::Uuid(uuid) => {
-                            let uuid = Uuid::from_bytes(uuid);
-                            folders.position(|folder| folder.id == Some(uuid))
-                        }
-                        ipc::Filter::Name(name) => folders.position(|folder| folder.name == name),
-                    };
+        if options.sort == Sort::Frecency {
+            for list in iter::once(&mut all).chain(type_buckets.iter_mut()) {
+                sort_by_frecency(list, &ciphers, &key, options.usage);
+            }
+        }
 
-                    index.map_or(View::List(List::All), View::Folder)
-                }
-                ipc::View::Cipher(filter) => {
-                    let index = match filter {
-                        ipc::Filter::Uuid(uuid) => {
-                            let uuid = Uuid::from_bytes(uuid);
-                            ciphers.position(|cipher| cipher.id == uuid)
-                        }
-                        ipc::Filter::Name(name) => ciphers.position(|cipher| cipher.name == name),
-                    };
+        let find_cipher = |filter| match filter {
+            ipc::Filter::Uuid(uuid) => {
+                let uuid = Uuid::from_bytes(uuid);
+                ciphers.position(|cipher: &Cipher| cipher.id == uuid)
+            }
+            ipc::Filter::Name(name) => ciphers.position(|cipher: &Cipher| cipher.name == name),
+        };
 
-                    index.map_or(View::List(List::All), View::Cipher)
-                }
-            }),
+        let domains = data.domains.unwrap_or_default();
+        let mut uri_views = Vec::<UriView>::new();
+
+        let history = history.map(|view| match view {
+            ipc::View::List(list) => View::List(list),
+            ipc::View::NoFolder => View::Folder(folders.last_index()),
+            ipc::View::Folder(filter) => {
+                let index = match filter {
+                    ipc::Filter::Uuid(uuid) => {
+                        let uuid = Uuid::from_bytes(uuid);
+                        folders.position(|folder| folder.id == Some(uuid))
+                    }
+                    ipc::Filter::Name(name) => folders.position(|folder| folder.name == name),
+                };
+
+                index.map_or(View::List(List::All), View::Folder)
+            }
+            ipc::View::Cipher(filter) => {
+                find_cipher(filter).map_or(View::List(List::All), View::Cipher)
+            }
+            ipc::View::PasswordHistory(filter) => {
+                find_cipher(filter).map_or(View::List(List::All), View::PasswordHistory)
+            }
+            ipc::View::Send(uuid) => {
+                let index = sends.position(|send| send.id == Uuid::from_bytes(uuid));
+                index.map_or(View::List(List::Sends), View::Send)
+            }
+            ipc::View::Uri(uri) => {
+                let existing = uri_views.iter().position(|view| view.page.uri() == uri);
+                View::Uri(existing.unwrap_or_else(|| {
+                    uri_views.push(UriView::new(uri, &domains, &ciphers, &all));
+                    uri_views.len() - 1
+                }))
+            }
+        });
+
+        let state = Self {
+            recent: recent_ciphers(&all, &ciphers, &key, options.usage),
+            history,
             ciphers,
             all,
             trash,
@@ -286,22 +833,31 @@ impl State {
             type_buckets,
             folders,
             folder_map,
-        })
+            sends,
+            uri_views,
+        };
+
+        Ok((state, folder_errors + cipher_errors + send_errors))
     }
 
     pub(crate) fn viewing(&self) -> Viewing<'_> {
         match *self.history.current() {
             View::List(list) => match list {
                 List::All => Viewing::CipherList(&self.all),
+                List::Recent => Viewing::CipherList(&self.recent),
                 List::Trash => Viewing::CipherList(&self.trash),
                 List::Favourites => Viewing::CipherList(&self.favourites),
                 List::TypeBucket(cipher_type) => {
                     Viewing::CipherList(&self.type_buckets[cipher_type])
                 }
                 List::Folders => Viewing::Folders(&self.folders),
+                List::Sends => Viewing::Sends(&self.sends),
             },
             View::Folder(i) => Viewing::CipherList(&self.folders[i].contents),
             View::Cipher(i) => Viewing::Cipher(&self.ciphers[i]),
+            View::PasswordHistory(i) => Viewing::PasswordHistory(&self.ciphers[i]),
+            View::Uri(i) => Viewing::CipherList(&self.uri_views[i].contents),
+            View::Send(i) => Viewing::Send(&self.sends[i]),
         }
     }
 }
@@ -310,17 +866,117 @@ enum Viewing<'a> {
     CipherList(&'a [typed_slice::Index<Cipher>]),
     Folders(&'a TypedSlice<Folder>),
     Cipher(&'a Cipher),
+    PasswordHistory(&'a Cipher),
+    Sends(&'a TypedSlice<Send>),
+    Send(&'a Send),
+}
+
+/// The items of `all` in the record’s recently used list, in its order.
+/// The name to give a folder from the input, or `None` if it is blank.
+fn folder_name(input: &str) -> Option<String> {
+    let name = input.trim();
+    (!name.is_empty()).then(|| name.to_owned())
+}
+
+fn recent_ciphers(
+    all: &[typed_slice::Index<Cipher>],
+    ciphers: &TypedSlice<Cipher>,
+    key: &SymmetricKey,
+    usage: &usage::Record,
+) -> Vec<typed_slice::Index<Cipher>> {
+    let recent = usage.recent();
+    let mut found = vec![None; recent.len()];
+    for &i in all {
+        let item = usage::ItemKey::new(key, ciphers[i].id.into_bytes());
+        if let Some(position) = recent.iter().position(|&recent| recent == item) {
+            found[position] = Some(i);
+        }
+    }
+    found.into_iter().flatten().collect()
+}
+
+/// Stably sort items so that those used often and recently come first.
+fn sort_by_frecency(
+    list: &mut [typed_slice::Index<Cipher>],
+    ciphers: &TypedSlice<Cipher>,
+    key: &SymmetricKey,
+    usage: &usage::Record,
+) {
+    let now = usage::now();
+    list.sort_by_cached_key(|&i| {
+        let item = usage::ItemKey::new(key, ciphers[i].id.into_bytes());
+        Reverse(usage.get(&item).map_or(0, |usage| usage.frecency(now)))
+    });
+}
+
+fn add_folders_to_search(
+    ciphers: &mut TypedSlice<Cipher>,
+    folders: &TypedSlice<Folder>,
+    folder_map: &FolderMap,
+) {
+    for cipher in ciphers {
+        if let Some(&folder) = folder_map.get(&cipher.folder_id) {
+            if folders[folder].id.is_some() {
+                cipher.search.push('\n');
+                cipher.search.push_str(&folders[folder].name);
+            }
+        }
+    }
+}
+
+/// Apply the item’s and the config’s choices of which field is copied from lists of items.
+fn choose_default_copies(
+    ciphers: &mut TypedSlice<Cipher>,
+    folders: &TypedSlice<Folder>,
+    folder_map: &FolderMap,
+    default_copy: &DefaultCopy,
+) {
+    for cipher in ciphers {
+        let copy_names =
+            (cipher.fields.iter().enumerate()).filter_map(|(i, field)| match &field.action {
+                Some(Action::Copy { name, data, .. }) => Some((i, &**name, &**data)),
+                _ => None,
+            });
+
+        let from_item = (copy_names.clone())
+            .find(|(_, name, _)| name.eq_ignore_ascii_case(default_copy::ITEM_FIELD))
+            .map(|(_, _, data)| data.trim());
+
+        let folder = (folder_map.get(&cipher.folder_id))
+            .filter(|&&folder| folders[folder].id.is_some())
+            .map(|&folder| &*folders[folder].name);
+        let from_config = default_copy.fields(cipher.r#type, &cipher.name, folder);
+
+        let chosen = from_item.into_iter().chain(from_config).find_map(|wanted| {
+            (copy_names.clone())
+                .find(|(_, name, _)| name.eq_ignore_ascii_case(wanted))
+                .map(|(i, _, _)| i)
+        });
+        if let Some(i) = chosen {
+            cipher.default_copy = Some(i);
+        }
+    }
 }
 
 fn process_folders(
-    folders: Vec<data::Folder>,
+    folders: Vec<Lenient<data::Folder>>,
     key: &SymmetricKey,
     collator: &Collator,
-) -> anyhow::Result<(Box<TypedSlice<Folder>>, FolderMap)> {
+) -> anyhow::Result<(Box<TypedSlice<Folder>>, FolderMap, usize)> {
     let mut processed = Vec::with_capacity(folders.len() + 1);
+    let mut errors = 0;
 
     for folder in folders {
-        processed.push(process_folder(folder, key)?);
+        let (id, result) = match folder {
+            Lenient::Parsed(folder) => (folder.id, process_folder(folder, key)),
+            Lenient::Unparseable { header, error } => {
+                (header.id.unwrap_or_default(), Err(error.into()))
+            }
+        };
+        processed.push(result.unwrap_or_else(|e| {
+            errors += 1;
+            Folder::undecryptable(id, e)
+        }));
     }
 
     try_sort::unstable_by(&mut processed, |a, b| -> anyhow::Result<_> {
@@ -342,25 +998,67 @@ fn process_folders(
         .map(|(i, folder)| (folder.id, i))
         .collect::<HashMap<Option<Uuid>, typed_slice::Index<Folder>>>();
 
-    Ok((processed, map))
+    Ok((processed, map, errors))
 }
 
 fn process_ciphers(
-    ciphers: Vec<data::Cipher>,
+    mut ciphers: Vec<Lenient<data::Cipher>>,
     key: &SymmetricKey,
     collator: &Collator,
-) -> anyhow::Result<Box<TypedSlice<Cipher>>> {
+    search_fields: ipc::SearchFields,
+) -> anyhow::Result<(Box<TypedSlice<Cipher>>, usize)> {
+    // Newer versions of Bitwarden may add cipher types we don’t know how to show; skip them
+    // rather than failing to show the whole vault.
+    ciphers.retain(|cipher| match cipher {
+        Lenient::Parsed(cipher) => !matches!(cipher.data, CipherData::Unknown),
+        Lenient::Unparseable { header, .. } => header.cipher_type().is_some(),
+    });
+
     let mut processed = (0..ciphers.len())
         .map(|_| Cipher::safe_uninit())
         .collect::<Box<[_]>>();
 
-    ciphers
+    let errors = ciphers
         .into_par_iter()
         .zip_eq(&mut *processed)
-        .try_for_each(|(cipher, out)| {
-            *out = process_cipher(cipher, key)?;
-            anyhow::Ok(())
-        })?;
+        .map(|(cipher, out)| {
+            let (placeholder, result) = match cipher {
+                Lenient::Parsed(cipher) => {
+                    let placeholder = Cipher {
+                        id: cipher.id,
+                        folder_id: cipher.folder_id,
+                        r#type: cipher_type(&cipher.data),
+                        deleted: cipher.deleted_date.is_some(),
+                        favourite: cipher.favourite,
+                        ..Cipher::safe_uninit()
+                    };
+                    (placeholder, process_cipher(cipher, key, search_fields))
+                }
+                Lenient::Unparseable { header, error } => {
+                    let placeholder = Cipher {
+                        id: header.id.unwrap_or_default(),
+                        folder_id: header.folder_id,
+                        r#type: header.cipher_type().unwrap(),
+                        deleted: header.deleted_date.is_some(),
+                        favourite: header.favorite,
+                        ..Cipher::safe_uninit()
+                    };
+                    (placeholder, Err(error.into()))
+                }
+            };
+            match result {
+                Ok(cipher) => {
+                    *out = cipher;
+                    false
+                }
+                Err(e) => {
+                    *out = placeholder.undecryptable(e);
+                    true
+                }
+            }
+        })
+        .filter(|&errored| errored)
+        .count();
 
     try_sort::unstable_by(&mut processed, |a, b| -> anyhow::Result<_> {
         Ok(collator
@@ -368,7 +1066,7 @@ fn process_ciphers(
             .then_with(|| a.id.cmp(&b.id)))
     })?;
 
-    Ok(TypedSlice::from_boxed_slice(processed))
+    Ok((TypedSlice::from_boxed_slice(processed), errors))
 }
 
 fn process_folder(folder: data::Folder, key: &SymmetricKey) -> anyhow::Result<Folder> {
@@ -386,16 +1084,198 @@ struct Folder {
     contents: Vec<typed_slice::Index<Cipher>>,
 }
 
-fn process_cipher(cipher: data::Cipher, key: &SymmetricKey) -> anyhow::Result<Cipher> {
+impl Folder {
+    /// A placeholder for a folder that failed to decrypt, so that its items can still be found.
+    fn undecryptable(id: Uuid, error: anyhow::Error) -> Self {
+        eprintln!(
+            "Warning: {:?}",
+            error.context(format!("failed to decrypt folder {id}"))
+        );
+        Self {
+            id: Some(id),
+            name: format!("Undecryptable folder ({id})"),
+            contents: Vec::new(),
+        }
+    }
+}
+
+fn process_sends(
+    sends: Vec<data::Send>,
+    key: &SymmetricKey,
+    collator: &Collator,
+) -> anyhow::Result<(Box<TypedSlice<Send>>, usize)> {
+    let mut errors = 0;
+    let mut processed = sends
+        .into_iter()
+        .map(|send| {
+            let id = send.id;
+            process_send(send, key).unwrap_or_else(|e| {
+                errors += 1;
+                Send::undecryptable(id, e)
+            })
+        })
+        .collect::<Vec<_>>();
+
+    try_sort::unstable_by(&mut processed, |a, b| -> anyhow::Result<_> {
+        Ok(collator
+            .strcoll_utf8(&a.name, &b.name)?
+            .then_with(|| a.id.cmp(&b.id)))
+    })?;
+
+    Ok((
+        TypedSlice::from_boxed_slice(processed.into_boxed_slice()),
+        errors,
+    ))
+}
+
+fn process_send(send: data::Send, key: &SymmetricKey) -> anyhow::Result<Send> {
+    let key_material = Zeroizing::new(send.key.decrypt(key)?);
+    let send_key = SymmetricKey::for_send(&key_material);
+
+    let name = send.name.decrypt(&send_key)?;
+
+    let mut fields = vec![Field::send_link(send::link(&send.access_id, &key_material))];
+
+    let (kind, icon) = match (send.text, send.file) {
+        (Some(text), _) => {
+            if let Some(content) = text.text {
+                fields.push(Field::send_text(content.decrypt(&send_key)?, text.hidden));
+            }
+            ("text", icons::Glyph::SecureNote)
+        }
+        (None, Some(file)) => {
+            let file_name = file.file_name.decrypt(&send_key)?;
+            fields.push(Field::send_file(&file_name, &file.size_name));
+            ("file", icons::Glyph::Paperclip)
+        }
+        (None, None) => anyhow::bail!("Send {name} has neither text nor a file"),
+    };
+
+    if let Some(notes) = send.notes {
+        fields.push(Field::notes(notes.decrypt(&send_key)?));
+    }
+
+    let expiry = match send.expiration_date {
+        Some(date) => format!("expires {}", date.date()),
+        None => format!("deleted {}", send.deletion_date.date()),
+    };
+    let accesses = match send.max_access_count {
+        Some(max) => format!("accessed {} of {max} times", send.access_count),
+        None => format!("accessed {} times", send.access_count),
+    };
+
+    let mut display = format!("{name} ({kind}, {expiry}, {accesses}");
+    if send.disabled {
+        display.push_str(", disabled");
+    }
+    display.push(')');
+
+    if let Some(date) = send.expiration_date {
+        fields.push(Field::info(
+            format!("Expires {}", date.date()),
+            icons::Glyph::Clock,
+        ));
+    }
+    let deletion = format!("Deleted {}", send.deletion_date.date());
+    fields.push(Field::info(deletion, icons::Glyph::Clock));
+    fields.push(Field::info(
+        format!("Accessed {accesses}"),
+        icons::Glyph::Hash,
+    ));
+
+    let id = send.id.into_bytes();
+    fields.push(Field::send_edit(SendEdit::SetDisabled {
+        id,
+        disabled: !send.disabled,
+    }));
+    fields.push(Field::send_edit(SendEdit::Delete { id }));
+
+    Ok(Send {
+        id: send.id,
+        name,
+        display,
+        icon: Icon::Glyph(icon),
+        fields,
+    })
+}
+
+struct Send {
+    id: Uuid,
+    name: String,
+    /// How the Send is shown in the list of Sends.
+    display: String,
+    icon: Icon,
+    fields: Vec<Field>,
+}
+
+impl Send {
+    /// The index of the field containing the Send’s link, which is copied by default.
+    const LINK_FIELD: usize = 0;
+
+    /// A placeholder for a Send that failed to decrypt, which shows the error and can be deleted.
+    fn undecryptable(id: Uuid, error: anyhow::Error) -> Self {
+        let error = error.context(format!("failed to decrypt Send {id}"));
+        eprintln!("Warning: {error:?}");
+        let name = format!("Undecryptable Send ({id})");
+        Self {
+            id,
+            display: name.clone(),
+            name,
+            icon: Icon::Glyph(icons::Glyph::Error),
+            // The error takes the place of the link, so that nothing is copied by default.
+            fields: vec![
+                Field::info(format!("{error:#}"), icons::Glyph::Error),
+                Field::send_edit(SendEdit::Delete {
+                    id: id.into_bytes(),
+                }),
+            ],
+        }
+    }
+}
+
+/// The type of a cipher, used to put placeholders for ciphers that failed to decrypt in the right
+/// type bucket.
+fn cipher_type(data: &CipherData) -> CipherType {
+    match data {
+        CipherData::Login(_) => CipherType::Login,
+        CipherData::SecureNote => CipherType::SecureNote,
+        CipherData::Card(_) => CipherType::Card,
+        CipherData::Identity(_) => CipherType::Identity,
+        CipherData::SshKey(_) => CipherType::SshKey,
+        CipherData::Unknown => unreachable!("ciphers of unknown type are skipped"),
+    }
+}
+
+fn process_cipher(
+    cipher: data::Cipher,
+    key: &SymmetricKey,
+    search_fields: ipc::SearchFields,
+) -> anyhow::Result<Cipher> {
     let name = cipher.name.decrypt(key)?;
+    let mut search = name.clone();
 
     let mut fields = Vec::new();
     let mut default_copy = None;
+    let mut login_uris = Vec::new();
+    let mut totp = None;
     let icon;
 
     let r#type = match cipher.data {
-        CipherData::Login(login) => {
-            icon = process_login(login, key, &mut fields, &mut default_copy)?;
+        CipherData::Login(mut login) => {
+            totp = login
+                .totp
+                .take()
+                .map(|totp| totp.decrypt(key))
+                .transpose()?;
+            icon = process_login(
+                login,
+                key,
+                &mut fields,
+                &mut default_copy,
+                &mut login_uris,
+                &mut search,
+                search_fields,
+            )?;
             CipherType::Login
         }
         CipherData::SecureNote => {
@@ -415,6 +1295,17 @@ fn process_cipher(cipher: data::Cipher, key: &SymmetricKey) -> anyhow::Result<Ci
             icon = process_identity(identity, key, &mut fields)?;
             CipherType::Identity
         }
+        CipherData::SshKey(ssh_key) => {
+            default_copy = Some(fields.len());
+            fields.push(Field::ssh_public_key(ssh_key.public_key.decrypt(key)?));
+            fields.push(Field::ssh_fingerprint(
+                ssh_key.key_fingerprint.decrypt(key)?,
+            ));
+            fields.push(Field::ssh_private_key(ssh_key.private_key.decrypt(key)?));
+            icon = Icon::Glyph(icons::Glyph::SshKey);
+            CipherType::SshKey
+        }
+        CipherData::Unknown => unreachable!("ciphers of unknown type are skipped"),
     };
 
     if let Some(notes) = cipher.notes {
@@ -422,21 +1313,27 @@ fn process_cipher(cipher: data::Cipher, key: &SymmetricKey) -> anyhow::Result<Ci
     }
 
     for custom_field in cipher.fields.into_iter().flatten() {
-        let name = match custom_field.name {
-            Some(name) => Some(name.decrypt(key)?),
-            None => None,
-        };
+        fields.push(process_custom_field(custom_field, key)?);
+    }
 
-        let value = match custom_field.value {
-            data::FieldValue::Text(Some(v)) => FieldValue::Text(Some(v.decrypt(key)?)),
-            data::FieldValue::Text(None) => FieldValue::Text(None),
-            data::FieldValue::Hidden(Some(v)) => FieldValue::Hidden(Some(v.decrypt(key)?)),
-            data::FieldValue::Hidden(None) => FieldValue::Hidden(None),
-            data::FieldValue::Boolean(v) => FieldValue::Boolean(v.decrypt(key)?),
-            data::FieldValue::Linked(v) => FieldValue::Linked(v),
-        };
+    for attachment in cipher.attachments.into_iter().flatten() {
+        let file_name = attachment.file_name.decrypt(key)?;
+        // The key is decrypted by the parent process, so that it never leaves it.
+        fields.push(Field::attachment(
+            attachment.id,
+            file_name,
+            &attachment.size_name,
+            attachment.key.map(|key| key.to_string()),
+        ));
+    }
 
-        fields.push(Field::custom(name, value));
+    let mut password_history = Vec::new();
+    for entry in cipher.password_history.into_iter().flatten() {
+        let password = entry.password.decrypt(key)?;
+        password_history.push(Field::old_password(password, entry.last_used_date));
+    }
+    if !password_history.is_empty() {
+        fields.push(Field::password_history(password_history.len()));
     }
 
     Ok(Cipher {
@@ -446,23 +1343,57 @@ fn process_cipher(cipher: data::Cipher, key: &SymmetricKey) -> anyhow::Result<Ci
         deleted: cipher.deleted_date.is_some(),
         favourite: cipher.favourite,
         name,
+        search,
         icon,
         reprompt: cipher.reprompt,
         fields,
         default_copy,
+        password_history,
+        login_uris,
+        totp,
     })
 }
 
+fn process_custom_field(custom_field: data::Field, key: &SymmetricKey) -> anyhow::Result<Field> {
+    let name = match custom_field.name {
+        Some(name) => Some(name.decrypt(key)?),
+        None => None,
+    };
+
+    let value = match custom_field.value {
+        data::FieldValue::Text(Some(v)) => FieldValue::Text(Some(v.decrypt(key)?)),
+        data::FieldValue::Text(None) => FieldValue::Text(None),
+        data::FieldValue::Hidden(Some(v)) => FieldValue::Hidden(Some(v.decrypt(key)?)),
+        data::FieldValue::Hidden(None) => FieldValue::Hidden(None),
+        data::FieldValue::Boolean(v) => FieldValue::Boolean(v.decrypt(key)?),
+        data::FieldValue::Linked(v) => FieldValue::Linked(v),
+    };
+
+    Ok(Field::custom(name, value))
+}
+
 fn process_login(
     login: data::Login,
     key: &SymmetricKey,
     fields: &mut Vec<Field>,
     default_copy: &mut Option<usize>,
+    login_uris: &mut Vec<LoginUri>,
+    search: &mut String,
+    search_fields: ipc::SearchFields,
 ) -> anyhow::Result<Icon> {
     let icon = extract_host(&login, key).map_or(Icon::Glyph(icons::Glyph::Login), Icon::Host);
 
+    let mut add_to_search = |s: &str| {
+        search.push('\n');
+        search.push_str(s);
+    };
+
     if let Some(username) = login.username {
-        fields.push(Field::username(username.decrypt(key)?));
+        let username = username.decrypt(key)?;
+        if search_fields.username {
+            add_to_search(&username);
+        }
+        fields.push(Field::username(username));
     }
 
     if let Some(password) = login.password {
@@ -471,7 +1402,24 @@ fn process_login(
     }
 
     for uri in login.uris.into_iter().flatten() {
-        fields.push(Field::uri(uri.uri.decrypt(key)?));
+        let match_type = uri.match_type;
+        let uri = uri.uri.decrypt(key)?;
+        if search_fields.uris {
+            add_to_search(&uri);
+        }
+        login_uris.push(LoginUri {
+            uri: uri.clone(),
+            match_type,
+        });
+        fields.push(Field::uri(uri));
+    }
+
+    for credential in login.fido2_credentials.into_iter().flatten() {
+        fields.push(Field::passkey_relying_party(credential.rp_id.decrypt(key)?));
+        if let Some(user_name) = credential.user_name {
+            fields.push(Field::passkey_user_name(user_name.decrypt(key)?));
+        }
+        fields.push(Field::passkey_creation_date(credential.creation_date));
     }
 
     Ok(icon)
@@ -580,6 +1528,8 @@ fn process_identity(
     Ok(Icon::Glyph(icons::Glyph::Identity))
 }
 
+/// The registrable domain of the login’s URI, so that logins for different subdomains of a site
+/// share an icon.
 fn extract_host(login: &data::Login, key: &SymmetricKey) -> Option<Arc<str>> {
     let url = login.uri.as_ref()?.decrypt(key).ok()?;
     let url = url.trim();
@@ -593,7 +1543,9 @@ fn extract_host(login: &data::Login, key: &SymmetricKey) -> Option<Arc<str>> {
     })?;
 
     match url.host()? {
-        url::Host::Domain(domain) => Some(<Arc<str>>::from(domain)),
+        url::Host::Domain(domain) => Some(<Arc<str>>::from(
+            domains::registrable(domain).unwrap_or(domain),
+        )),
         _ => None,
     }
 }
@@ -601,7 +1553,13 @@ fn extract_host(login: &data::Login, key: &SymmetricKey) -> Option<Arc<str>> {
 use cipher_type_list::CipherTypeList;
 mod cipher_type_list {
     #[derive(Default)]
-    pub(crate) struct CipherTypeList<T>([T; 4]);
+    pub(crate) struct CipherTypeList<T>([T; 5]);
+
+    impl<T> CipherTypeList<T> {
+        pub(crate) fn iter_mut(&mut self) -> slice::IterMut<'_, T> {
+            self.0.iter_mut()
+        }
+    }
 
     impl<T> ops::Index<CipherType> for CipherTypeList<T> {
         type Output = T;
@@ -618,6 +1576,7 @@ mod cipher_type_list {
 
     use super::CipherType;
     use std::ops;
+    use std::slice;
 }
 
 struct Cipher {
@@ -628,10 +1587,24 @@ struct Cipher {
     deleted: bool,
     favourite: bool,
     name: String,
+    /// What the filter matches against in lists of items: the name, followed by the other
+    /// searchable fields on their own lines.
+    search: String,
     icon: Icon,
     reprompt: bool,
     fields: Vec<Field>,
     default_copy: Option<usize>,
+    /// Previous passwords of the cipher, shown in their own view.
+    password_history: Vec<Field>,
+    /// The URIs of a login, used to find the logins for a page.
+    login_uris: Vec<LoginUri>,
+    /// The authenticator key of a login, from which TOTP codes are generated when copied.
+    totp: Option<String>,
+}
+
+struct LoginUri {
+    uri: String,
+    match_type: Option<UriMatchType>,
 }
 
 impl Cipher {
@@ -643,12 +1616,54 @@ impl Cipher {
             deleted: false,
             favourite: false,
             name: String::new(),
+            search: String::new(),
             icon: Icon::Glyph(icons::Glyph::Login),
             reprompt: false,
             fields: Vec::new(),
             default_copy: None,
+            password_history: Vec::new(),
+            login_uris: Vec::new(),
+            totp: None,
         }
     }
+
+    fn copy_request(
+        &self,
+        icons: &mut Icons,
+        step: CopyStep,
+        then: Vec<CopyStep>,
+        hidden: bool,
+        menu_state: ipc::menu_request::MenuState,
+    ) -> ipc::MenuRequest {
+        let image_path = icons
+            .fs_path(&self.icon)
+            .and_then(|path| std::fs::canonicalize(path).ok())
+            .and_then(|path| path.into_os_string().into_string().ok());
+
+        ipc::MenuRequest::Copy {
+            cipher_name: self.name.clone(),
+            cipher_id: Some(self.id.into_bytes()),
+            field: step.field,
+            data: step.data,
+            then,
+            secret: hidden,
+            other_selection: false,
+            image_path,
+            reprompt: hidden && self.reprompt,
+            menu_state,
+        }
+    }
+
+    /// Turn a cipher that failed to decrypt into a placeholder that shows the error.
+    fn undecryptable(mut self, error: anyhow::Error) -> Self {
+        let error = error.context(format!("failed to decrypt item {}", self.id));
+        eprintln!("Warning: {error:?}");
+        self.name = format!("Undecryptable item ({})", self.id);
+        self.search.clone_from(&self.name);
+        self.icon = Icon::Glyph(icons::Glyph::Error);
+        self.fields = vec![Field::info(format!("{error:#}"), icons::Glyph::Error)];
+        self
+    }
 }
 
 struct Field {
@@ -664,6 +1679,85 @@ impl Field {
     fn password(password: String) -> Self {
         Self::hidden("Password", "password", password, icons::Glyph::Key)
     }
+    fn old_password(password: String, last_used: OffsetDateTime) -> Self {
+        Self {
+            display: Cow::Owned(format!("Password last used {}", last_used.date())),
+            icon: Icon::Glyph(icons::Glyph::Key),
+            action: Some(Action::Copy {
+                name: Cow::Borrowed("old password"),
+                data: password,
+                hidden: true,
+            }),
+        }
+    }
+    fn password_history(len: usize) -> Self {
+        Self {
+            display: Cow::Owned(format!("Password history ({len})")),
+            icon: Icon::Glyph(icons::Glyph::Clock),
+            action: Some(Action::ShowPasswordHistory),
+        }
+    }
+    fn attachment(id: String, file_name: String, size: &str, key: Option<String>) -> Self {
+        Self {
+            display: Cow::Owned(format!("Attachment: {file_name} ({size})")),
+            icon: Icon::Glyph(icons::Glyph::Paperclip),
+            action: Some(Action::SaveAttachment { id, file_name, key }),
+        }
+    }
+    fn send_link(link: String) -> Self {
+        Self::shown("Link", "link", link, icons::Glyph::Chain)
+    }
+    fn send_text(text: String, hidden: bool) -> Self {
+        if hidden {
+            Self::hidden("Text", "text", text, icons::Glyph::SecureNote)
+        } else {
+            Self::shown("Text", "text", text, icons::Glyph::SecureNote)
+        }
+    }
+    fn send_file(file_name: &str, size: &str) -> Self {
+        Self::info(
+            format!("File: {file_name} ({size})"),
+            icons::Glyph::Paperclip,
+        )
+    }
+    fn send_edit(edit: SendEdit) -> Self {
+        let (display, glyph) = match edit {
+            SendEdit::SetDisabled { disabled: true, .. } => ("Disable", icons::Glyph::Ban),
+            SendEdit::SetDisabled {
+                disabled: false, ..
+            } => ("Enable", icons::Glyph::SquareCheck),
+            SendEdit::Delete { .. } => ("Delete", icons::Glyph::Trash),
+        };
+        Self {
+            display: Cow::Borrowed(display),
+            icon: Icon::Glyph(glyph),
+            action: Some(Action::EditSend(edit)),
+        }
+    }
+    fn passkey_relying_party(relying_party: String) -> Self {
+        let icon = icons::Glyph::Passkey;
+        Self::shown("Passkey", "passkey site", relying_party, icon)
+    }
+    fn passkey_user_name(user_name: String) -> Self {
+        let icon = icons::Glyph::User;
+        Self::shown("Passkey user name", "passkey user name", user_name, icon)
+    }
+    fn passkey_creation_date(created: OffsetDateTime) -> Self {
+        let display = format!("Passkey created {}", created.date());
+        Self::info(display, icons::Glyph::Clock)
+    }
+    fn ssh_public_key(public_key: String) -> Self {
+        let icon = icons::Glyph::Key;
+        Self::shown("Public key", "public key", public_key, icon)
+    }
+    fn ssh_fingerprint(fingerprint: String) -> Self {
+        let icon = icons::Glyph::Hash;
+        Self::shown("Fingerprint", "fingerprint", fingerprint, icon)
+    }
+    fn ssh_private_key(private_key: String) -> Self {
+        let icon = icons::Glyph::Padlock;
+        Self::hidden("Private key", "private key", private_key, icon)
+    }
     fn uri(uri: String) -> Self {
         Self::shown("Uri", "URI", uri, icons::Glyph::Chain)
     }
@@ -848,6 +1942,15 @@ impl Field {
         }
     }
 
+    /// A field that is only shown, and does nothing when selected.
+    fn info(display: String, icon: impl Into<Icon>) -> Self {
+        Self {
+            display: Cow::Owned(display),
+            icon: icon.into(),
+            action: None,
+        }
+    }
+
     fn shown(title: &'static str, name: &'static str, data: String, icon: impl Into<Icon>) -> Self {
         Self {
             display: Cow::Owned(format!("{title}: {data}")),
@@ -895,6 +1998,14 @@ enum Action {
     Link {
         to: &'static str,
     },
+    ShowPasswordHistory,
+    SaveAttachment {
+        id: String,
+        file_name: String,
+        /// Encrypted with the user key.
+        key: Option<String>,
+    },
+    EditSend(SendEdit),
 }
 
 use typed_slice::TypedSlice;
@@ -1086,23 +2197,48 @@ mod try_sort {
 use crate::data;
 use crate::data::CipherData;
 use crate::data::Data;
+use crate::data::Lenient;
+use crate::error_status;
 use crate::icons;
 use crate::Icon;
 use crate::Icons;
 use crate::SymmetricKey;
-use anyhow::Context as _;
 use rayon::iter::IntoParallelIterator;
 use rayon::iter::ParallelIterator;
 use rayon::prelude::IndexedParallelIterator;
+use rofi_bw_common::default_copy;
+use rofi_bw_common::default_copy::DefaultCopy;
+use rofi_bw_common::domains;
+use rofi_bw_common::domains::Domains;
 use rofi_bw_common::ipc;
+use rofi_bw_common::ipc::menu_request::CopyStep;
+use rofi_bw_common::ipc::menu_request::FolderEdit;
+use rofi_bw_common::ipc::menu_request::SendEdit;
+use rofi_bw_common::keybind;
 use rofi_bw_common::menu_keybinds::Navigate;
+use rofi_bw_common::menu_keybinds::QuickCopy;
+use rofi_bw_common::send;
+use rofi_bw_common::totp;
+use rofi_bw_common::uri_match;
+use rofi_bw_common::uri_match::UriMatchType;
+use rofi_bw_common::usage;
+use rofi_bw_common::usage::Sort;
 use rofi_bw_common::CipherType;
+use rofi_bw_common::Keybind;
 use rofi_bw_common::List;
 use rofi_bw_common::MasterKey;
 use rofi_bw_util::History;
 use rofi_mode::cairo;
+use rofi_mode::pango::glib;
 use std::borrow::Cow;
+use std::cmp::Reverse;
 use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::iter;
 use std::sync::Arc;
+use std::time::Instant;
+use std::time::SystemTime;
+use time::OffsetDateTime;
 use url::Url;
 use uuid::Uuid;
+use zeroize::Zeroizing;
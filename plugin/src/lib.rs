@@ -24,6 +24,8 @@ struct Mode<'rofi> {
     #[allow(dead_code)]
     api: rofi_mode::Api<'rofi>,
     pipe: Option<BufWriter<UnixStream>>,
+    /// Ordered like `MENU_KEYBINDS`, with the combinations rofi was started with.
+    keybinds: Vec<Keybind<menu_keybinds::Action>>,
     state: State,
 }
 
@@ -40,6 +42,20 @@ impl Mode<'_> {
         }
     }
 
+    fn entry_search_text(&self, line: usize) -> &str {
+        match &self.state {
+            State::Initialized(initialized) => initialized.entry_search_text(line),
+            State::Errored(_) => panic!("this mode has no entries"),
+        }
+    }
+
+    fn initialized(&self) -> Option<&Initialized> {
+        match &self.state {
+            State::Initialized(initialized) => Some(initialized),
+            _ => None,
+        }
+    }
+
     fn initialized_mut(&mut self) -> Option<&mut Initialized> {
         match &mut self.state {
             State::Initialized(initialized) => Some(initialized),
@@ -47,12 +63,73 @@ impl Mode<'_> {
         }
     }
 
+    /// Send the request to the parent process and exit if there is one, otherwise reload the menu.
+    fn respond(&mut self, request: Option<MenuRequest>) -> rofi_mode::Action {
+        match request {
+            Some(request) => {
+                send_request(&mut self.pipe, &request);
+                rofi_mode::Action::Exit
+            }
+            None => rofi_mode::Action::Reload,
+        }
+    }
+
+    /// Run the action of a custom keybind.
+    fn run_action(
+        &mut self,
+        action: menu_keybinds::Action,
+        selected: Option<usize>,
+        input: &mut rofi_mode::String,
+    ) -> rofi_mode::Action {
+        let request = match action {
+            menu_keybinds::Action::ShowList(list) => {
+                if let Some(initialized) = self.initialized_mut() {
+                    initialized.show(list);
+                }
+                None
+            }
+            menu_keybinds::Action::Parent => {
+                if let Some(initialized) = self.initialized_mut() {
+                    initialized.parent();
+                }
+                None
+            }
+            menu_keybinds::Action::Sync => Some(ipc::MenuRequest::Sync {
+                menu_state: self.menu_state(input),
+            }),
+            menu_keybinds::Action::Lock => Some(ipc::MenuRequest::Lock),
+            menu_keybinds::Action::LogOut => Some(ipc::MenuRequest::LogOut),
+            menu_keybinds::Action::Navigate(navigate) => {
+                if let Some(initialized) = self.initialized_mut() {
+                    initialized.navigate(navigate);
+                }
+                None
+            }
+            menu_keybinds::Action::CreateSend => self
+                .initialized_mut()
+                .and_then(|initialized| initialized.create_send(selected, input)),
+            menu_keybinds::Action::Copy(field) => self
+                .initialized_mut()
+                .zip(selected)
+                .and_then(|(initialized, line)| initialized.quick_copy(line, field, input)),
+            menu_keybinds::Action::CopyOtherSelection => self
+                .initialized_mut()
+                .zip(selected)
+                .and_then(|(initialized, line)| initialized.ok_other_selection(line, input)),
+            menu_keybinds::Action::RenameFolder => self
+                .initialized_mut()
+                .zip(selected)
+                .and_then(|(initialized, line)| initialized.rename_folder(line, input)),
+        };
+        self.respond(request)
+    }
+
     fn menu_state(&self, input: &str) -> ipc::menu_request::MenuState {
-        ipc::menu_request::MenuState {
-            filter: input.to_string(),
-            history: match &self.state {
-                State::Initialized(initialized) => initialized.ipc_state(),
-                State::Errored(_) => History::default(),
+        match &self.state {
+            State::Initialized(initialized) => initialized.menu_state(input.to_string()),
+            State::Errored(_) => ipc::menu_request::MenuState {
+                filter: input.to_string(),
+                ..ipc::menu_request::MenuState::default()
             },
         }
     }
@@ -62,6 +139,7 @@ impl<'rofi> rofi_mode::Mode<'rofi> for Mode<'rofi> {
     const NAME: &'static str = "bw\0";
     fn init(mut api: rofi_mode::Api<'rofi>) -> Result<Self, ()> {
         let mut pipe = None;
+        let mut keybinds = None;
 
         let res = (|| {
             let pipe = BufReader::new(pipe.insert(get_pipe()?));
@@ -69,9 +147,23 @@ impl<'rofi> rofi_mode::Mode<'rofi> for Mode<'rofi> {
                 master_key,
                 data,
                 history,
+                usage,
+                sort,
+                lock_in,
+                search_fields,
+                keybinds: combinations,
+                default_copy,
             } = ipc::handshake::read(pipe)?;
+            keybinds = Some(combinations.keybinds());
+            let lock_at = lock_in.map(|secs| Instant::now() + Duration::from_secs(secs));
             let data = serde_json::from_slice(&data).context("failed to read vault data")?;
-            Initialized::new(&master_key, data, history)
+            let options = initialized::Options {
+                search_fields,
+                usage: &usage,
+                sort,
+                default_copy: &default_copy,
+            };
+            Initialized::new(&master_key, data, history, lock_at, &options)
         })();
 
         let state = res
@@ -84,8 +176,15 @@ impl<'rofi> rofi_mode::Mode<'rofi> for Mode<'rofi> {
         });
 
         let pipe = pipe.map(BufWriter::new);
-
-        Ok(Self { api, pipe, state })
+        let keybinds =
+            keybinds.unwrap_or_else(|| menu_keybinds::Combinations::default().keybinds());
+
+        Ok(Self {
+            api,
+            pipe,
+            keybinds,
+            state,
+        })
     }
 
     fn entries(&mut self) -> usize {
@@ -111,6 +210,16 @@ impl<'rofi> rofi_mode::Mode<'rofi> for Mode<'rofi> {
         event: rofi_mode::Event,
         input: &mut rofi_mode::String,
     ) -> rofi_mode::Action {
+        // Only pressing the same key again confirms a pending action.
+        if !matches!(
+            event,
+            rofi_mode::Event::CustomInput { .. } | rofi_mode::Event::DeleteEntry { .. }
+        ) {
+            if let Some(initialized) = self.initialized_mut() {
+                initialized.cancel_pending();
+            }
+        }
+
         match event {
             rofi_mode::Event::Cancel { selected: _ } => {
                 let menu_state = self.menu_state(input);
@@ -126,13 +235,7 @@ impl<'rofi> rofi_mode::Mode<'rofi> for Mode<'rofi> {
                         initialized.ok(selected, input)
                     };
 
-                    match request {
-                        Some(request) => {
-                            send_request(&mut self.pipe, &request);
-                            rofi_mode::Action::Exit
-                        }
-                        None => rofi_mode::Action::Reload,
-                    }
+                    self.respond(request)
                 }
                 State::Errored(_) => panic!("this mode has no entries"),
             },
@@ -143,63 +246,51 @@ impl<'rofi> rofi_mode::Mode<'rofi> for Mode<'rofi> {
                 input.push_str(self.entry_content(selected));
                 rofi_mode::Action::Reload
             }
-            rofi_mode::Event::CustomCommand {
-                number,
-                selected: _,
-            } => {
-                let keybind = match MENU_KEYBINDS.get(usize::from(number)) {
-                    Some(keybind) => keybind,
-                    None => return rofi_mode::Action::Reload,
-                };
-                let request = match keybind.action {
-                    menu_keybinds::Action::ShowList(list) => {
-                        if let Some(initialized) = self.initialized_mut() {
-                            initialized.show(list);
-                        }
-                        return rofi_mode::Action::Reload;
-                    }
-                    menu_keybinds::Action::Parent => {
-                        if let Some(initialized) = self.initialized_mut() {
-                            initialized.parent();
-                        }
-                        return rofi_mode::Action::Reload;
-                    }
-                    menu_keybinds::Action::Sync => ipc::MenuRequest::Sync {
-                        menu_state: self.menu_state(input),
-                    },
-                    menu_keybinds::Action::Lock => ipc::MenuRequest::Lock,
-                    menu_keybinds::Action::LogOut => ipc::MenuRequest::LogOut,
-                    menu_keybinds::Action::Navigate(navigate) => {
-                        if let Some(initialized) = self.initialized_mut() {
-                            initialized.navigate(navigate);
-                        }
-                        return rofi_mode::Action::Reload;
-                    }
-                };
-                send_request(&mut self.pipe, &request);
-                rofi_mode::Action::Exit
+            rofi_mode::Event::CustomCommand { number, selected } => {
+                let mut enabled = self.keybinds.iter().filter(|keybind| keybind.is_enabled());
+                match enabled.nth(usize::from(number)) {
+                    Some(keybind) => self.run_action(keybind.action, selected, input),
+                    None => rofi_mode::Action::Reload,
+                }
             }
             rofi_mode::Event::CustomInput {
                 alt: _,
                 selected: _,
+            } => {
+                let request = self
+                    .initialized_mut()
+                    .and_then(|initialized| initialized.create_folder(input));
+                self.respond(request)
             }
-            | rofi_mode::Event::Complete { selected: None }
-            | rofi_mode::Event::DeleteEntry { selected: _ } => rofi_mode::Action::Reload,
+            rofi_mode::Event::DeleteEntry { selected } => {
+                let request = self
+                    .initialized_mut()
+                    .and_then(|initialized| initialized.delete_entry(selected, input));
+                self.respond(request)
+            }
+            rofi_mode::Event::Complete { selected: None } => rofi_mode::Action::Reload,
         }
     }
 
     fn matches(&self, line: usize, matcher: rofi_mode::Matcher<'_>) -> bool {
-        matcher.matches(self.entry_content(line))
+        matcher.matches(self.entry_search_text(line))
     }
 
     fn message(&mut self) -> rofi_mode::String {
         let mut message = rofi_mode::String::new();
 
         if self.pipe.is_some() {
-            let history = self.initialized_mut().map(|i| i.history());
-            menu_keybinds::keybinds_ui(history, |row| {
-                writeln!(message, "{}", keybind::HelpMarkup(row)).unwrap();
-            });
+            let initialized = self.initialized();
+            let viewing_folders = initialized.is_some_and(Initialized::viewing_folders);
+            let viewing_items = initialized.is_some_and(Initialized::viewing_items);
+            let history = initialized.map(Initialized::history);
+            menu_keybinds::keybinds_ui(
+                &self.keybinds,
+                history,
+                viewing_folders,
+                viewing_items,
+                |row| writeln!(message, "{}", keybind::HelpMarkup(row)).unwrap(),
+            );
         }
 
         writeln!(message).unwrap();
@@ -291,15 +382,6 @@ mod icons;
 
 mod data;
 
-use cipher_string::CipherString;
-mod cipher_string;
-
-use symmetric_key::SymmetricKey;
-mod symmetric_key;
-
-use base64_decode_array::base64_decode_array;
-mod base64_decode_array;
-
 use disk_cache::DiskCache;
 mod disk_cache;
 
@@ -317,11 +399,14 @@ use rofi_bw_common::ipc;
 use rofi_bw_common::ipc::MenuRequest;
 use rofi_bw_common::keybind;
 use rofi_bw_common::menu_keybinds;
-use rofi_bw_common::MENU_KEYBINDS;
-use rofi_bw_util::History;
+use rofi_bw_common::CipherString;
+use rofi_bw_common::Keybind;
+use rofi_bw_common::SymmetricKey;
 use rofi_mode::cairo;
 use std::fmt::Write as _;
 use std::io::BufReader;
 use std::io::BufWriter;
 use std::io::Write;
 use std::os::unix::net::UnixStream;
+use std::time::Duration;
+use std::time::Instant;
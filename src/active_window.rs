@@ -0,0 +1,107 @@
+//! Finding the URI of the page open in the focused window, for `--from-active-window`.
+
+/// Read the URI from the output of `active_window.command` if it is set, or otherwise from the
+/// title of the focused X11 window.
+///
+/// Browsers don’t put the URI in the title by default, so this needs an extension that does.
+pub(crate) fn uri(display: &Display, options: &config::ActiveWindow) -> anyhow::Result<String> {
+    let text = match &options.command {
+        Some(command) => run_command(command, display)?,
+        None => {
+            let x11 = display
+                .x11
+                .as_deref()
+                .context("without X11, `active_window.command` must be set")?;
+            window_title(x11)?
+        }
+    };
+    let uri = find_uri(&text).with_context(|| format!("no URI found in {text:?}"))?;
+    Ok(uri.to_owned())
+}
+
+/// Run the command on the display the menu was requested from, which may not be the daemon’s.
+fn run_command(command: &[String], display: &Display) -> anyhow::Result<String> {
+    let (program, args) = command
+        .split_first()
+        .context("`active_window.command` is empty")?;
+    let mut command = process::Command::new(program);
+    command.args(args).stderr(process::Stdio::inherit());
+    display.apply_to_env(&mut command);
+    let output = command
+        .output()
+        .with_context(|| format!("failed to run {program}"))?;
+    anyhow::ensure!(
+        output.status.success(),
+        "{program} exited with {}",
+        output.status
+    );
+    String::from_utf8(output.stdout).with_context(|| format!("output of {program} is not UTF-8"))
+}
+
+/// The `_NET_WM_NAME` of the window the window manager reports as active.
+fn window_title(display: &str) -> anyhow::Result<String> {
+    let (connection, screen) =
+        x11rb::connect(Some(display)).context("failed to connect to X server")?;
+    let root = connection.setup().roots[screen].root;
+
+    let atom = |name: &[u8]| -> anyhow::Result<Atom> {
+        Ok(connection
+            .intern_atom(false, name)
+            .context("failed to intern atom")?
+            .reply()
+            .context("failed to intern atom")?
+            .atom)
+    };
+    let active_window = atom(b"_NET_ACTIVE_WINDOW")?;
+    let name = atom(b"_NET_WM_NAME")?;
+    let utf8_string = atom(b"UTF8_STRING")?;
+
+    let window = connection
+        .get_property(false, root, active_window, AtomEnum::WINDOW, 0, 1)
+        .context("failed to query active window")?
+        .reply()
+        .context("failed to query active window")?
+        .value32()
+        .and_then(|mut values| values.next())
+        .filter(|&window| window != x11rb::NONE)
+        .context("there is no active window")?;
+
+    let title = connection
+        .get_property(false, window, name, utf8_string, 0, u32::MAX)
+        .context("failed to query window title")?
+        .reply()
+        .context("failed to query window title")?
+        .value;
+    String::from_utf8(title).context("window title is not UTF-8")
+}
+
+/// The first word of `text` that is an absolute HTTP or HTTPS URI.
+fn find_uri(text: &str) -> Option<&str> {
+    text.split_whitespace()
+        .map(|word| word.trim_matches(|c| matches!(c, '(' | ')' | '[' | ']' | '<' | '>' | '"')))
+        .find(|word| Url::parse(word).is_ok_and(|url| matches!(url.scheme(), "http" | "https")))
+}
+
+#[test]
+fn finds_uri() {
+    assert_eq!(
+        find_uri("Sign in - https://example.com/login?next=%2F - Mozilla Firefox"),
+        Some("https://example.com/login?next=%2F"),
+    );
+    assert_eq!(
+        find_uri("GitHub (https://github.com/)"),
+        Some("https://github.com/")
+    );
+    assert_eq!(find_uri("Example Domain — Mozilla Firefox"), None);
+    assert_eq!(find_uri("file:///etc/hosts"), None);
+}
+
+use crate::config;
+use crate::daemon::Display;
+use anyhow::Context as _;
+use std::process;
+use url::Url;
+use x11rb::connection::Connection as _;
+use x11rb::protocol::xproto::Atom;
+use x11rb::protocol::xproto::AtomEnum;
+use x11rb::protocol::xproto::ConnectionExt as _;
@@ -0,0 +1,109 @@
+/// Decrypt the key of an attachment. Very old attachments don’t have one and are encrypted with
+/// the user key directly.
+pub(crate) fn key(
+    user_key: &SymmetricKey,
+    key: Option<&CipherString<Vec<u8>>>,
+) -> anyhow::Result<SymmetricKey> {
+    let Some(key) = key else {
+        return Ok(user_key.clone());
+    };
+    let bytes = Zeroizing::new(key.decrypt(user_key)?);
+    SymmetricKey::from_slice(&bytes).context("attachment key is the wrong size")
+}
+
+/// Decrypt the downloaded contents of an attachment using its key.
+pub(crate) fn decrypt(key: &SymmetricKey, data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    (|| {
+        let encrypted = cipher_string::Untyped::from_buffer(data)?;
+        anyhow::Ok(encrypted.decrypt(key)?)
+    })()
+    .context("failed to decrypt attachment")
+}
+
+/// Save an attachment in the given directory, returning the path it was saved to.
+///
+/// The file is only readable by the current user. Existing files are never overwritten; instead
+/// a number is appended to the file name.
+pub(crate) fn save(
+    dir: &fs::Path,
+    file_name: &str,
+    contents: &[u8],
+) -> anyhow::Result<fs::PathBuf> {
+    // The file name is chosen by whoever uploaded the attachment, so make sure it can’t escape the
+    // directory.
+    let file_name = fs::Path::new(file_name)
+        .file_name()
+        .unwrap_or_else(|| OsStr::new("attachment"));
+    let file_name = fs::Path::new(file_name);
+
+    fs::create_dir_all(dir)?;
+
+    let stem = file_name.file_stem().unwrap_or_default();
+    let extension = file_name.extension();
+
+    for i in 0..100 {
+        let mut name = stem.to_owned();
+        if i > 0 {
+            name.push(format!(" ({i})"));
+        }
+        if let Some(extension) = extension {
+            name.push(".");
+            name.push(extension);
+        }
+        let path = dir.join(name);
+
+        let access = fs::file::open::Access::WriteOnly(fs::file::open::WriteOptions::CreateNew);
+        let options = fs::file::open::Options::from_access(access).mode(0o600);
+        let mut file = match fs::file::open(path, options) {
+            Ok(file) => file,
+            Err(e) if e.source.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e.into()),
+        };
+
+        if let Err(e) = file.write_all(contents) {
+            drop(std::fs::remove_file(file.path()));
+            return Err(e.into());
+        }
+
+        return Ok(file.into_path());
+    }
+
+    anyhow::bail!("too many files named {} exist", file_name.display())
+}
+
+/// Pipe an attachment to the standard input of a user-specified command.
+pub(crate) fn open_with(command: &[String], contents: &[u8]) -> anyhow::Result<()> {
+    let (program, args) = command.split_first().context("opener command is empty")?;
+
+    let mut child = process::Command::new(program)
+        .args(args)
+        .stdin(process::Stdio::piped())
+        .spawn()
+        .with_context(|| format!("failed to spawn {program}"))?;
+
+    let mut stdin = child.stdin.take().unwrap();
+    // Write on a separate thread so that an opener that doesn’t read its input can’t block us.
+    let contents = Zeroizing::new(contents.to_owned());
+    thread::spawn(move || {
+        if let Err(e) = stdin.write_all(&contents) {
+            eprintln!("Warning: failed to write attachment to opener: {e}");
+        }
+    });
+
+    // Don’t wait for the opener to exit; it may be a long-lived program like an image viewer.
+    thread::spawn(move || drop(child.wait()));
+
+    Ok(())
+}
+
+use anyhow::Context as _;
+use rofi_bw_common::cipher_string;
+use rofi_bw_common::CipherString;
+use rofi_bw_common::SymmetricKey;
+use rofi_bw_util::fs;
+use std::ffi::OsStr;
+use std::io;
+use std::io::Write as _;
+use std::process;
+use std::thread;
+use zeroize::Zeroizing;
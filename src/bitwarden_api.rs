@@ -17,7 +17,7 @@ impl<'http, 'access_token, 'base_url> Client<'http, 'access_token, 'base_url> {
     pub(crate) fn sync(self) -> Result<String, SyncError> {
         let data = self
             .http
-            .get(&format!("{}/sync?excludeDomains=true", &self.base_url))
+            .get(&format!("{}/sync", &self.base_url))
             .set("Authorization", &format!("Bearer {}", self.access_token))
             .set("Accept", "application/json")
             .call()?
@@ -25,6 +25,223 @@ impl<'http, 'access_token, 'base_url> Client<'http, 'access_token, 'base_url> {
 
         Ok(data)
     }
+
+    pub(crate) fn create_folder(self, name: &str) -> Result<(), EditFolderError> {
+        self.http
+            .post(&format!("{}/folders", self.base_url))
+            .set("Authorization", &format!("Bearer {}", self.access_token))
+            .send_json(FolderBody { name })?;
+        Ok(())
+    }
+
+    pub(crate) fn rename_folder(self, id: Uuid, name: &str) -> Result<(), EditFolderError> {
+        self.http
+            .put(&format!("{}/folders/{id}", self.base_url))
+            .set("Authorization", &format!("Bearer {}", self.access_token))
+            .send_json(FolderBody { name })?;
+        Ok(())
+    }
+
+    /// Delete a folder. The server moves any items in the folder to “No folder”.
+    pub(crate) fn delete_folder(self, id: Uuid) -> Result<(), EditFolderError> {
+        self.http
+            .delete(&format!("{}/folders/{id}", self.base_url))
+            .set("Authorization", &format!("Bearer {}", self.access_token))
+            .call()?;
+        Ok(())
+    }
+
+    /// Download the still-encrypted contents of an attachment.
+    pub(crate) fn download_attachment(
+        self,
+        cipher_id: Uuid,
+        attachment_id: &str,
+    ) -> Result<Vec<u8>, DownloadAttachmentError> {
+        let AttachmentData { url } = self
+            .http
+            .get(&format!(
+                "{}/ciphers/{cipher_id}/attachment/{attachment_id}",
+                self.base_url
+            ))
+            .set("Authorization", &format!("Bearer {}", self.access_token))
+            .set("Accept", "application/json")
+            .call()?
+            .into_json()?;
+
+        // The URL is pre-signed, so it doesn’t need the access token.
+        let mut data = Vec::new();
+        self.http
+            .get(&url)
+            .call()?
+            .into_reader()
+            .read_to_end(&mut data)?;
+
+        Ok(data)
+    }
+
+    /// Replace a Send with the given one, which is in the same format as the `/sync` response.
+    pub(crate) fn update_send(
+        self,
+        id: Uuid,
+        send: &serde_json::Value,
+    ) -> Result<(), EditSendError> {
+        self.http
+            .put(&format!("{}/sends/{id}", self.base_url))
+            .set("Authorization", &format!("Bearer {}", self.access_token))
+            .send_json(send)?;
+        Ok(())
+    }
+
+    pub(crate) fn delete_send(self, id: Uuid) -> Result<(), EditSendError> {
+        self.http
+            .delete(&format!("{}/sends/{id}", self.base_url))
+            .set("Authorization", &format!("Bearer {}", self.access_token))
+            .call()?;
+        Ok(())
+    }
+
+    /// Create a text Send, returning its access ID.
+    pub(crate) fn create_send(self, body: &SendBody) -> Result<String, CreateSendError> {
+        let SendResponse { access_id, .. } = self
+            .http
+            .post(&format!("{}/sends", self.base_url))
+            .set("Authorization", &format!("Bearer {}", self.access_token))
+            .set("Accept", "application/json")
+            .send_json(body)?
+            .into_json()?;
+        Ok(access_id)
+    }
+
+    /// Create a file Send and upload its encrypted contents, returning its access ID.
+    pub(crate) fn create_file_send(
+        self,
+        body: &SendBody,
+        data: &[u8],
+    ) -> Result<String, CreateSendError> {
+        let FileUploadData {
+            url,
+            file_upload_type,
+            send_response,
+        } = self
+            .http
+            .post(&format!("{}/sends/file/v2", self.base_url))
+            .set("Authorization", &format!("Bearer {}", self.access_token))
+            .set("Accept", "application/json")
+            .send_json(body)?
+            .into_json()?;
+
+        match file_upload_type {
+            0 => {
+                let file_id = send_response.file.ok_or(CreateSendError::MissingFileId)?.id;
+                let file_name = body.file.as_ref().map_or("", |file| &*file.file_name);
+
+                let boundary = format!("--rofi-bw-{:032x}", rand::random::<u128>());
+                let mut multipart = Vec::with_capacity(data.len() + 256);
+                write!(
+                    multipart,
+                    "--{boundary}\r\n\
+                    Content-Disposition: form-data; name=\"data\"; filename=\"{file_name}\"\r\n\
+                    Content-Type: application/octet-stream\r\n\r\n"
+                )
+                .unwrap();
+                multipart.extend_from_slice(data);
+                write!(multipart, "\r\n--{boundary}--\r\n").unwrap();
+
+                self.http
+                    .post(&format!(
+                        "{}/sends/{}/file/{file_id}",
+                        self.base_url, send_response.id
+                    ))
+                    .set("Authorization", &format!("Bearer {}", self.access_token))
+                    .set(
+                        "Content-Type",
+                        &format!("multipart/form-data; boundary={boundary}"),
+                    )
+                    .send_bytes(&multipart)?;
+            }
+            1 => {
+                // The URL is pre-signed, so it doesn’t need the access token.
+                self.http
+                    .put(&url)
+                    .set("x-ms-blob-type", "BlockBlob")
+                    .set("x-ms-version", "2020-04-08")
+                    .send_bytes(data)?;
+            }
+            other => return Err(CreateSendError::UnsupportedUploadType(other)),
+        }
+
+        Ok(send_response.access_id)
+    }
+}
+
+/// The body of a request to create a Send. All user-provided fields are encrypted.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SendBody {
+    /// 0 for text Sends, 1 for file Sends.
+    pub(crate) r#type: u8,
+    pub(crate) name: String,
+    pub(crate) notes: Option<String>,
+    /// The Send’s key material, encrypted with the user’s key.
+    pub(crate) key: String,
+    pub(crate) max_access_count: Option<u32>,
+    /// An RFC 3339 timestamp.
+    pub(crate) expiration_date: Option<String>,
+    /// An RFC 3339 timestamp.
+    pub(crate) deletion_date: String,
+    pub(crate) text: Option<SendText>,
+    pub(crate) file: Option<SendFile>,
+    /// The length of the encrypted file, for file Sends.
+    pub(crate) file_length: Option<u64>,
+    /// The hash of the password protecting the Send.
+    pub(crate) password: Option<String>,
+    pub(crate) disabled: bool,
+    pub(crate) hide_email: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SendText {
+    pub(crate) text: String,
+    pub(crate) hidden: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SendFile {
+    pub(crate) file_name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SendResponse {
+    id: String,
+    access_id: String,
+    file: Option<SendFileResponse>,
+}
+
+#[derive(Deserialize)]
+struct SendFileResponse {
+    id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FileUploadData {
+    url: String,
+    /// 0 if the file is to be uploaded directly to Bitwarden, 1 if it goes to Azure.
+    file_upload_type: u8,
+    send_response: SendResponse,
+}
+
+#[derive(Deserialize)]
+struct AttachmentData {
+    url: String,
+}
+
+#[derive(Serialize)]
+struct FolderBody<'name> {
+    name: &'name str,
 }
 
 #[derive(Debug)]
@@ -60,7 +277,127 @@ impl std::error::Error for SyncError {
     }
 }
 
+#[derive(Debug)]
+pub(crate) struct EditFolderError(Box<ureq::Error>);
+
+impl From<ureq::Error> for EditFolderError {
+    fn from(error: ureq::Error) -> Self {
+        Self(Box::new(error))
+    }
+}
+
+impl Display for EditFolderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("failed to edit folder on Bitwarden server")
+    }
+}
+
+impl std::error::Error for EditFolderError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct EditSendError(Box<ureq::Error>);
+
+impl From<ureq::Error> for EditSendError {
+    fn from(error: ureq::Error) -> Self {
+        Self(Box::new(error))
+    }
+}
+
+impl Display for EditSendError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("failed to edit Send on Bitwarden server")
+    }
+}
+
+impl std::error::Error for EditSendError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) enum DownloadAttachmentError {
+    Http(Box<ureq::Error>),
+    Body(io::Error),
+}
+
+impl From<ureq::Error> for DownloadAttachmentError {
+    fn from(error: ureq::Error) -> Self {
+        Self::Http(Box::new(error))
+    }
+}
+
+impl From<io::Error> for DownloadAttachmentError {
+    fn from(error: io::Error) -> Self {
+        Self::Body(error)
+    }
+}
+
+impl Display for DownloadAttachmentError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("failed to download attachment from Bitwarden server")
+    }
+}
+
+impl std::error::Error for DownloadAttachmentError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Http(e) => Some(e),
+            Self::Body(e) => Some(e),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) enum CreateSendError {
+    Http(Box<ureq::Error>),
+    Body(io::Error),
+    MissingFileId,
+    UnsupportedUploadType(u8),
+}
+
+impl From<ureq::Error> for CreateSendError {
+    fn from(error: ureq::Error) -> Self {
+        Self::Http(Box::new(error))
+    }
+}
+
+impl From<io::Error> for CreateSendError {
+    fn from(error: io::Error) -> Self {
+        Self::Body(error)
+    }
+}
+
+impl Display for CreateSendError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingFileId => f.write_str("Bitwarden server did not give an ID for the file"),
+            Self::UnsupportedUploadType(n) => write!(f, "unsupported file upload type {n}"),
+            _ => f.write_str("failed to create Send on Bitwarden server"),
+        }
+    }
+}
+
+impl std::error::Error for CreateSendError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Http(e) => Some(e),
+            Self::Body(e) => Some(e),
+            Self::MissingFileId | Self::UnsupportedUploadType(_) => None,
+        }
+    }
+}
+
+use serde::Deserialize;
+use serde::Serialize;
 use std::fmt;
 use std::fmt::Display;
 use std::fmt::Formatter;
 use std::io;
+use std::io::Read as _;
+use std::io::Write as _;
+use uuid::Uuid;
@@ -0,0 +1,475 @@
+//! Copying to the clipboard of whichever display the menu was opened on.
+
+/// Uses the X11 selections when there is an X display, and otherwise `wl-copy`, which supports
+/// the `wlr-data-control` protocol so that copying works without a focused window.
+#[derive(Default)]
+pub(crate) struct Clipboard {
+    /// Incremented by every copy, so that a sequence stops once something else has been copied.
+    generation: Arc<AtomicU64>,
+}
+
+/// How long a copy sequence waits for each text to be pasted before moving on to the next.
+const STEP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long after a text is copied that requests for it aren’t counted as pastes, since clipboard
+/// managers read every new text as soon as it is copied.
+const PASTE_GRACE_PERIOD: Duration = Duration::from_millis(300);
+
+/// How often a copy sequence checks whether its timeout has passed.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+impl Clipboard {
+    pub(crate) fn set_text(
+        &mut self,
+        display: &Display,
+        text: String,
+        target: ClipboardTarget,
+    ) -> anyhow::Result<()> {
+        self.set_sequence(display, vec![text], target, false, |_| {})
+    }
+
+    /// Copy each of the texts in turn, replacing each one with the next once it has been pasted or
+    /// after `STEP_TIMEOUT`. `on_step` is called with the index of every text after the first as
+    /// it is copied.
+    ///
+    /// Secret texts are marked with the `x-kde-passwordManagerHint` target so that clipboard
+    /// managers don’t record them. `wl-copy` can only offer one type, so this only works on X11.
+    pub(crate) fn set_sequence(
+        &mut self,
+        display: &Display,
+        texts: Vec<String>,
+        target: ClipboardTarget,
+        secret: bool,
+        on_step: impl FnMut(usize) + Send + 'static,
+    ) -> anyhow::Result<()> {
+        assert!(!texts.is_empty(), "no text to copy");
+        let texts = texts.into_iter().map(Zeroizing::new).collect::<Vec<_>>();
+
+        let generation = self.generation.fetch_add(1, atomic::Ordering::SeqCst) + 1;
+        let current = Arc::clone(&self.generation);
+        let is_current = move || current.load(atomic::Ordering::SeqCst) == generation;
+
+        match (&display.x11, &display.wayland) {
+            (Some(x11), _) => x11::serve(x11, texts, target, secret, on_step),
+            (None, Some(wayland)) => wl_copy_sequence(wayland, texts, target, on_step, is_current),
+            (None, None) => unreachable!("no display"),
+        }
+        .context("failed to set clipboard content")
+    }
+}
+
+/// Copies the first text with `wl-copy --paste-once`, which exits after the first paste, and then
+/// the rest from a background thread as each previous one is pasted.
+///
+/// `wl-copy` can’t tell which client read the text, so unlike on X11 a clipboard manager reading
+/// it counts as pasting it.
+fn wl_copy_sequence(
+    wayland_display: &str,
+    texts: Vec<Zeroizing<String>>,
+    target: ClipboardTarget,
+    mut on_step: impl FnMut(usize) + Send + 'static,
+    is_current: impl Fn() -> bool + Send + 'static,
+) -> anyhow::Result<()> {
+    if let [text] = &*texts {
+        return wl_copy(wayland_display, text, target);
+    }
+
+    let mut pasting = spawn_paste_once(wayland_display, &texts[0], target)?;
+    let wayland_display = wayland_display.to_owned();
+
+    thread::spawn(move || {
+        let res = (|| {
+            for (i, text) in texts.iter().enumerate().skip(1) {
+                wait_for_paste(&mut pasting)?;
+                // A later copy replaced this one, or `wl-copy` wouldn’t have exited.
+                if !is_current() {
+                    return Ok(());
+                }
+                if i == texts.len() - 1 {
+                    wl_copy(&wayland_display, text, target)?;
+                } else {
+                    pasting = spawn_paste_once(&wayland_display, text, target)?;
+                }
+                on_step(i);
+            }
+            anyhow::Ok(())
+        })();
+        if let Err(e) = res {
+            eprintln!(
+                "Warning: {:?}",
+                e.context("failed to continue copy sequence")
+            );
+        }
+    });
+
+    Ok(())
+}
+
+/// Start a `wl-copy --paste-once` for each selection of the target.
+fn spawn_paste_once(
+    wayland_display: &str,
+    text: &str,
+    target: ClipboardTarget,
+) -> anyhow::Result<Vec<process::Child>> {
+    (selection_args(target).iter())
+        .map(|args| {
+            let args = [args, &["--foreground", "--paste-once"][..]].concat();
+            spawn_wl_copy(wayland_display, text, &args)
+        })
+        .collect()
+}
+
+/// Wait for one of the `wl-copy --paste-once`s to exit, or for `STEP_TIMEOUT` to pass, then kill
+/// the rest.
+fn wait_for_paste(pasting: &mut [process::Child]) -> anyhow::Result<()> {
+    let deadline = Instant::now() + STEP_TIMEOUT;
+    loop {
+        let mut exited = false;
+        for wl_copy in &mut *pasting {
+            exited |= wl_copy
+                .try_wait()
+                .context("failed to wait on wl-copy")?
+                .is_some();
+        }
+        if exited || Instant::now() >= deadline {
+            break;
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+
+    for wl_copy in pasting {
+        if wl_copy
+            .try_wait()
+            .context("failed to wait on wl-copy")?
+            .is_none()
+        {
+            wl_copy.kill().context("failed to kill wl-copy")?;
+            wl_copy.wait().context("failed to wait on wl-copy")?;
+        }
+    }
+    Ok(())
+}
+
+fn wl_copy(wayland_display: &str, text: &str, target: ClipboardTarget) -> anyhow::Result<()> {
+    for args in selection_args(target) {
+        let mut wl_copy = spawn_wl_copy(wayland_display, text, args)?;
+
+        // wl-copy forks to serve the clipboard in the background, so this doesn’t wait for long.
+        let status = wl_copy.wait().context("failed to wait on wl-copy")?;
+        anyhow::ensure!(status.success(), "wl-copy exited with {status}");
+    }
+
+    Ok(())
+}
+
+/// The arguments `wl-copy` is run with for each selection of the target.
+const fn selection_args(target: ClipboardTarget) -> &'static [&'static [&'static str]] {
+    match target {
+        ClipboardTarget::Clipboard => &[&[]],
+        ClipboardTarget::Primary => &[&["--primary"]],
+        ClipboardTarget::Both => &[&[], &["--primary"]],
+    }
+}
+
+fn spawn_wl_copy(
+    wayland_display: &str,
+    text: &str,
+    args: &[&str],
+) -> anyhow::Result<process::Child> {
+    let mut wl_copy = process::Command::new("wl-copy");
+    wl_copy.args(args);
+    wl_copy.env("WAYLAND_DISPLAY", wayland_display);
+    wl_copy.stdin(process::Stdio::piped());
+    let mut wl_copy = wl_copy.spawn().context("failed to spawn wl-copy")?;
+
+    let mut stdin = wl_copy.stdin.take().unwrap();
+    stdin
+        .write_all(text.as_bytes())
+        .context("failed to write to wl-copy")?;
+    drop(stdin);
+
+    Ok(wl_copy)
+}
+
+mod x11 {
+    /// Take ownership of the target’s selections and serve the texts from a background thread,
+    /// moving on to the next text each time one is pasted or `STEP_TIMEOUT` passes. The thread
+    /// exits once other copies, from this process or any other, have taken all the selections
+    /// over.
+    pub(super) fn serve(
+        display: &str,
+        texts: Vec<Zeroizing<String>>,
+        target: ClipboardTarget,
+        secret: bool,
+        on_step: impl FnMut(usize) + Send + 'static,
+    ) -> anyhow::Result<()> {
+        let (connection, screen) =
+            x11rb::connect(Some(display)).context("failed to connect to X server")?;
+        let root = connection.setup().roots[screen].root;
+
+        let atoms = Atoms::new(&connection)
+            .context("failed to intern atoms")?
+            .reply()
+            .context("failed to intern atoms")?;
+
+        let window = connection
+            .generate_id()
+            .context("failed to generate window ID")?;
+        connection
+            .create_window(
+                COPY_DEPTH_FROM_PARENT,
+                window,
+                root,
+                0,
+                0,
+                1,
+                1,
+                0,
+                WindowClass::INPUT_OUTPUT,
+                COPY_FROM_PARENT,
+                &CreateWindowAux::new(),
+            )
+            .context("failed to create window")?;
+
+        let selections: &[Atom] = match target {
+            ClipboardTarget::Clipboard => &[atoms.CLIPBOARD],
+            ClipboardTarget::Primary => &[atoms.PRIMARY],
+            ClipboardTarget::Both => &[atoms.CLIPBOARD, atoms.PRIMARY],
+        };
+        for &selection in selections {
+            connection
+                .set_selection_owner(window, selection, CURRENT_TIME)
+                .context("failed to take selection")?;
+            let owner = connection
+                .get_selection_owner(selection)
+                .context("failed to query selection owner")?
+                .reply()
+                .context("failed to query selection owner")?
+                .owner;
+            anyhow::ensure!(owner == window, "failed to take selection");
+        }
+
+        let owner = Owner {
+            connection,
+            atoms,
+            owned: selections.len(),
+            texts,
+            secret,
+            step: 0,
+            step_started: Instant::now(),
+            on_step,
+        };
+        thread::spawn(move || {
+            if let Err(e) = owner.run() {
+                eprintln!("Warning: {:?}", e.context("failed to serve clipboard"));
+            }
+        });
+
+        Ok(())
+    }
+
+    x11rb::atom_manager! {
+        Atoms: AtomsCookie {
+            CLIPBOARD,
+            PRIMARY,
+            TARGETS,
+            UTF8_STRING,
+            UTF8_MIME_0: b"text/plain;charset=utf-8",
+            UTF8_MIME_1: b"text/plain;charset=UTF-8",
+            PASSWORD_MANAGER_HINT: b"x-kde-passwordManagerHint",
+            CLIPBOARD_MANAGER,
+        }
+    }
+
+    struct Owner<OnStep> {
+        connection: RustConnection,
+        atoms: Atoms,
+        /// The number of selections still owned.
+        owned: usize,
+        texts: Vec<Zeroizing<String>>,
+        secret: bool,
+        /// The index of the text currently being served.
+        step: usize,
+        step_started: Instant,
+        on_step: OnStep,
+    }
+
+    impl<OnStep: FnMut(usize)> Owner<OnStep> {
+        fn run(mut self) -> anyhow::Result<()> {
+            let mut deadline = self.deadline();
+            loop {
+                let event = match deadline {
+                    None => Some(self.connection.wait_for_event()?),
+                    Some(deadline) => match self.connection.poll_for_event()? {
+                        Some(event) => Some(event),
+                        None if Instant::now() >= deadline => None,
+                        None => {
+                            thread::sleep(POLL_INTERVAL);
+                            continue;
+                        }
+                    },
+                };
+
+                match event {
+                    Some(Event::SelectionRequest(request)) => {
+                        let is_paste = self.is_paste(&request)?;
+                        if self.reply(request)? && is_paste && deadline.is_some() {
+                            deadline = self.advance();
+                        }
+                    }
+                    Some(Event::SelectionClear(_)) => {
+                        self.owned -= 1;
+                        if self.owned == 0 {
+                            return Ok(());
+                        }
+                    }
+                    Some(_) => {}
+                    None => deadline = self.advance(),
+                }
+            }
+        }
+
+        /// When to move on to the next text if the current one isn’t pasted, or `None` if it is
+        /// the last.
+        fn deadline(&self) -> Option<Instant> {
+            (self.step + 1 < self.texts.len()).then(|| Instant::now() + STEP_TIMEOUT)
+        }
+
+        fn advance(&mut self) -> Option<Instant> {
+            self.step += 1;
+            self.step_started = Instant::now();
+            (self.on_step)(self.step);
+            self.deadline()
+        }
+
+        /// Whether a request comes from the user pasting rather than from a clipboard manager
+        /// saving the text.
+        fn is_paste(&self, request: &SelectionRequestEvent) -> anyhow::Result<bool> {
+            if self.step_started.elapsed() < PASTE_GRACE_PERIOD {
+                return Ok(false);
+            }
+            let clipboard_manager = self
+                .connection
+                .get_selection_owner(self.atoms.CLIPBOARD_MANAGER)?
+                .reply()?
+                .owner;
+            Ok(request.requestor != clipboard_manager)
+        }
+
+        /// Answer a request for the clipboard’s contents, returning whether the text was given
+        /// out.
+        fn reply(&self, request: SelectionRequestEvent) -> anyhow::Result<bool> {
+            let atoms = &self.atoms;
+            let text = &self.texts[self.step];
+
+            // Obsolete clients don’t give a property, in which case the target is used.
+            let property = if request.property == x11rb::NONE {
+                request.target
+            } else {
+                request.property
+            };
+
+            let text_targets = [atoms.UTF8_STRING, atoms.UTF8_MIME_0, atoms.UTF8_MIME_1];
+            // Texts too large to send in one request would need the INCR protocol.
+            let max_len = self.connection.maximum_request_bytes() - 24;
+
+            let served = if request.target == atoms.TARGETS {
+                let mut targets = vec![atoms.TARGETS];
+                targets.extend(text_targets);
+                if self.secret {
+                    targets.push(atoms.PASSWORD_MANAGER_HINT);
+                }
+                self.connection.change_property32(
+                    PropMode::REPLACE,
+                    request.requestor,
+                    property,
+                    AtomEnum::ATOM,
+                    &targets,
+                )?;
+                Some(false)
+            } else if self.secret && request.target == atoms.PASSWORD_MANAGER_HINT {
+                self.connection.change_property8(
+                    PropMode::REPLACE,
+                    request.requestor,
+                    property,
+                    request.target,
+                    b"secret",
+                )?;
+                Some(false)
+            } else if text_targets.contains(&request.target) && text.len() <= max_len {
+                self.connection.change_property8(
+                    PropMode::REPLACE,
+                    request.requestor,
+                    property,
+                    request.target,
+                    text.as_bytes(),
+                )?;
+                Some(true)
+            } else {
+                None
+            };
+
+            self.connection.send_event(
+                false,
+                request.requestor,
+                EventMask::NO_EVENT,
+                SelectionNotifyEvent {
+                    response_type: SELECTION_NOTIFY_EVENT,
+                    sequence: 0,
+                    time: request.time,
+                    requestor: request.requestor,
+                    selection: request.selection,
+                    target: request.target,
+                    property: if served.is_some() {
+                        property
+                    } else {
+                        x11rb::NONE
+                    },
+                },
+            )?;
+            self.connection.flush()?;
+
+            Ok(served == Some(true))
+        }
+    }
+
+    use super::PASTE_GRACE_PERIOD;
+    use super::POLL_INTERVAL;
+    use super::STEP_TIMEOUT;
+    use crate::config::ClipboardTarget;
+    use anyhow::Context as _;
+    use std::thread;
+    use std::time::Instant;
+    use x11rb::connection::Connection as _;
+    use x11rb::connection::RequestConnection as _;
+    use x11rb::protocol::xproto::Atom;
+    use x11rb::protocol::xproto::AtomEnum;
+    use x11rb::protocol::xproto::ConnectionExt as _;
+    use x11rb::protocol::xproto::CreateWindowAux;
+    use x11rb::protocol::xproto::EventMask;
+    use x11rb::protocol::xproto::PropMode;
+    use x11rb::protocol::xproto::SelectionNotifyEvent;
+    use x11rb::protocol::xproto::SelectionRequestEvent;
+    use x11rb::protocol::xproto::WindowClass;
+    use x11rb::protocol::xproto::SELECTION_NOTIFY_EVENT;
+    use x11rb::protocol::Event;
+    use x11rb::rust_connection::RustConnection;
+    use x11rb::wrapper::ConnectionExt as _;
+    use x11rb::COPY_DEPTH_FROM_PARENT;
+    use x11rb::COPY_FROM_PARENT;
+    use x11rb::CURRENT_TIME;
+    use zeroize::Zeroizing;
+}
+
+use crate::config::ClipboardTarget;
+use crate::daemon::Display;
+use anyhow::Context as _;
+use std::io::Write as _;
+use std::process;
+use std::sync::atomic;
+use std::sync::atomic::AtomicU64;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+use zeroize::Zeroizing;
@@ -22,14 +22,47 @@ fn load_inner(path: &fs::Path) -> anyhow::Result<Config> {
 #[serde(deny_unknown_fields)]
 pub(crate) struct Config {
     #[serde(default)]
-    pub(crate) auto_lock: AutoLock,
+    pub(crate) auto_lock: AutoLockOptions,
 
     #[serde(default = "returns_true")]
     pub(crate) copy_notification: bool,
 
+    #[serde(default)]
+    pub(crate) clipboard_target: ClipboardTarget,
+
+    #[serde(default)]
+    pub(crate) sort: Sort,
+
     #[serde(default)]
     pub(crate) rofi_options: RofiOptions,
 
+    #[serde(default)]
+    pub(crate) attachments: Attachments,
+
+    #[serde(default)]
+    pub(crate) send: Send,
+
+    #[serde(default)]
+    pub(crate) ssh_agent: SshAgent,
+
+    #[serde(default)]
+    pub(crate) get: Get,
+
+    #[serde(default)]
+    pub(crate) lock_on: LockOn,
+
+    #[serde(default)]
+    pub(crate) search: Search,
+
+    #[serde(default)]
+    pub(crate) active_window: ActiveWindow,
+
+    #[serde(default, with = "keybinds")]
+    pub(crate) keybinds: menu_keybinds::Combinations,
+
+    #[serde(default)]
+    pub(crate) default_copy: DefaultCopy,
+
     #[serde(default = "desktop_string")]
     pub(crate) client_id: String,
 
@@ -144,6 +177,134 @@ impl Default for RofiOptions {
     }
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Attachments {
+    #[serde(default)]
+    pub(crate) dir: Option<fs::PathBuf>,
+
+    #[serde(default)]
+    pub(crate) opener: Option<Vec<String>>,
+}
+
+impl Default for Attachments {
+    fn default() -> Self {
+        serde_default()
+    }
+}
+
+/// Defaults for Sends that are created.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Send {
+    #[serde(default = "seven_days", deserialize_with = "duration::deserialize")]
+    pub(crate) deletion: Duration,
+
+    #[serde(default, deserialize_with = "duration::deserialize_option")]
+    pub(crate) expiry: Option<Duration>,
+
+    #[serde(default)]
+    pub(crate) max_access_count: Option<u32>,
+}
+
+impl Default for Send {
+    fn default() -> Self {
+        serde_default()
+    }
+}
+
+/// The SSH agent that serves SSH keys stored in the vault.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct SshAgent {
+    #[serde(default)]
+    pub(crate) enabled: bool,
+}
+
+impl Default for SshAgent {
+    fn default() -> Self {
+        serde_default()
+    }
+}
+
+/// Options for `rofi-bw get`, `rofi-bw git-credential` and `rofi-bw list`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Get {
+    #[serde(default = "returns_true")]
+    pub(crate) confirm: bool,
+}
+
+impl Default for Get {
+    fn default() -> Self {
+        serde_default()
+    }
+}
+
+/// Events other than the auto-lock timer that lock the vault.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct LockOn {
+    #[serde(default)]
+    pub(crate) sleep: bool,
+
+    #[serde(default)]
+    pub(crate) screen_lock: bool,
+
+    #[serde(default, deserialize_with = "duration::deserialize_option")]
+    pub(crate) idle: Option<Duration>,
+}
+
+impl Default for LockOn {
+    fn default() -> Self {
+        serde_default()
+    }
+}
+
+/// Which fields of an item the filter matches against in the menu, besides its name.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Search {
+    #[serde(default = "returns_true")]
+    pub(crate) uris: bool,
+
+    #[serde(default = "returns_true")]
+    pub(crate) username: bool,
+
+    #[serde(default = "returns_true")]
+    pub(crate) folder: bool,
+}
+
+impl Default for Search {
+    fn default() -> Self {
+        serde_default()
+    }
+}
+
+impl Search {
+    pub(crate) fn fields(&self) -> ipc::SearchFields {
+        ipc::SearchFields {
+            uris: self.uris,
+            username: self.username,
+            folder: self.folder,
+        }
+    }
+}
+
+/// How `--from-active-window` finds the URI of the focused window.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ActiveWindow {
+    #[serde(default)]
+    pub(crate) command: Option<Vec<String>>,
+}
+
+impl Default for ActiveWindow {
+    fn default() -> Self {
+        serde_default()
+    }
+}
+
 #[derive(Debug, Clone, Copy, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub(crate) enum ScrollMethod {
@@ -161,7 +322,110 @@ pub(crate) enum Matching {
     Prefix,
 }
 
+/// Which selections copied values are put in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ClipboardTarget {
+    #[default]
+    Clipboard,
+    /// The selection pasted with the middle mouse button.
+    Primary,
+    Both,
+}
+
+impl ClipboardTarget {
+    /// The target of copies made with the `copy_other_selection` keybind.
+    pub(crate) const fn other(self) -> Self {
+        match self {
+            Self::Clipboard => Self::Primary,
+            Self::Primary => Self::Clipboard,
+            Self::Both => Self::Both,
+        }
+    }
+}
+
+mod keybinds {
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<menu_keybinds::Combinations, D::Error> {
+        deserializer.deserialize_map(Visitor)
+    }
+
+    struct Visitor;
+    impl<'de> de::Visitor<'de> for Visitor {
+        type Value = menu_keybinds::Combinations;
+        fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            f.write_str("a table of key combinations")
+        }
+        fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut combinations = menu_keybinds::Combinations::default();
+            while let Some((name, combination)) = map.next_entry::<String, String>()? {
+                if !combinations.set(&name, combination) {
+                    return Err(de::Error::custom(DeError { name: &name }));
+                }
+            }
+            if combinations.enabled() > keybind::MAX_ENABLED {
+                return Err(de::Error::custom(format_args!(
+                    "at most {} keybinds can be enabled; disable others by setting them to \"\"",
+                    keybind::MAX_ENABLED,
+                )));
+            }
+            Ok(combinations)
+        }
+    }
+
+    struct DeError<'name> {
+        name: &'name str,
+    }
+    impl Display for DeError<'_> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            write!(f, "unknown keybind `{}`, expected one of ", self.name)?;
+            for (i, keybind) in MENU_KEYBINDS.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(", ")?;
+                }
+                f.write_str(keybind.action.name())?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_keybinds() {
+        #[derive(serde::Deserialize)]
+        struct Config {
+            #[serde(with = "super::keybinds")]
+            keybinds: menu_keybinds::Combinations,
+        }
+        let parse = |s| toml::from_str::<Config>(s).map(|config| config.keybinds);
+
+        let combinations = parse("[keybinds]\nlock = 'Control+l'\nlog_out = ''").unwrap();
+        let keybinds = combinations.keybinds();
+        let combination = |name| {
+            let keybind = keybinds.iter().find(|k| k.action.name() == name).unwrap();
+            &*keybind.combination
+        };
+        assert_eq!(combination("lock"), "Control+l");
+        assert_eq!(combination("log_out"), "");
+        assert_eq!(combination("sync"), "Control+s");
+
+        assert!(parse("[keybinds]\nunknown = 'Alt+z'").is_err());
+        assert!(parse("[keybinds]\ncopy_uri = 'Alt+j'").is_err());
+        assert!(parse("[keybinds]\ncopy_uri = 'Alt+j'\ntrash = ''").is_ok());
+    }
+
+    use rofi_bw_common::keybind;
+    use rofi_bw_common::menu_keybinds;
+    use rofi_bw_common::MENU_KEYBINDS;
+    use serde::de;
+    use serde::Deserializer;
+    use std::fmt;
+    use std::fmt::Display;
+    use std::fmt::Formatter;
+}
+
 pub(crate) use auto_lock::AutoLock;
+pub(crate) use auto_lock::AutoLockOptions;
 mod auto_lock {
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub(crate) enum AutoLock {
@@ -175,6 +439,71 @@ mod auto_lock {
         }
     }
 
+    impl AutoLock {
+        /// Whether the timeout has run out, counting from `start`.
+        pub(crate) fn has_passed(self, start: Instant) -> bool {
+            match self {
+                Self::Never => false,
+                Self::After(timeout) => start.elapsed() >= timeout,
+            }
+        }
+    }
+
+    /// Either just the idle timeout, or a table of the idle timeout and the maximum session age.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub(crate) struct AutoLockOptions {
+        /// How long after the menu was last closed the vault locks.
+        pub(crate) idle: AutoLock,
+        /// How long after unlocking the vault it locks, whatever the user is doing.
+        pub(crate) max: AutoLock,
+    }
+
+    impl Default for AutoLockOptions {
+        fn default() -> Self {
+            Self {
+                idle: AutoLock::default(),
+                max: AutoLock::Never,
+            }
+        }
+    }
+
+    impl<'de> Deserialize<'de> for AutoLockOptions {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_any(OptionsVisitor)
+        }
+    }
+
+    struct OptionsVisitor;
+    impl<'de> de::Visitor<'de> for OptionsVisitor {
+        type Value = AutoLockOptions;
+        fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            f.write_str("a duration, `never` or a table of `idle` and `max`")
+        }
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+            Ok(AutoLockOptions {
+                idle: de::Visitor::visit_str(Visitor, v)?,
+                ..AutoLockOptions::default()
+            })
+        }
+        fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+            #[derive(Deserialize)]
+            #[serde(deny_unknown_fields)]
+            struct Table {
+                #[serde(default)]
+                idle: AutoLock,
+                #[serde(default = "never")]
+                max: AutoLock,
+            }
+            fn never() -> AutoLock {
+                AutoLock::Never
+            }
+
+            let Table { idle, max } =
+                Table::deserialize(de::value::MapAccessDeserializer::new(map))?;
+            Ok(AutoLockOptions { idle, max })
+        }
+    }
+
     impl<'de> Deserialize<'de> for AutoLock {
         fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
             deserializer.deserialize_str(Visitor)
@@ -197,20 +526,7 @@ mod auto_lock {
             return Some(AutoLock::Never);
         }
 
-        let s = s.trim();
-
-        let multiplier = match s.chars().last()? {
-            'h' => 60 * 60,
-            'm' => 60,
-            's' => 1,
-            _ => return None,
-        };
-
-        let number = s[..s.len() - 1].parse::<u64>().ok()?;
-
-        let seconds = number.checked_mul(multiplier)?;
-
-        Some(AutoLock::After(Duration::from_secs(seconds)))
+        duration::parse(s).map(AutoLock::After)
     }
 
     #[test]
@@ -233,12 +549,110 @@ mod auto_lock {
         );
     }
 
+    #[test]
+    fn test_options() {
+        #[derive(Deserialize)]
+        struct Config {
+            auto_lock: AutoLockOptions,
+        }
+        let parse = |s| {
+            toml::from_str::<Config>(s)
+                .map(|config| config.auto_lock)
+                .ok()
+        };
+        let minutes = |n: u64| AutoLock::After(Duration::from_secs(n * 60));
+
+        assert_eq!(
+            parse("auto_lock = '5m'"),
+            Some(AutoLockOptions {
+                idle: minutes(5),
+                max: AutoLock::Never
+            })
+        );
+        assert_eq!(
+            parse("auto_lock = { idle = '5m', max = '8h' }"),
+            Some(AutoLockOptions {
+                idle: minutes(5),
+                max: minutes(8 * 60)
+            })
+        );
+        assert_eq!(
+            parse("auto_lock = { max = 'never' }"),
+            Some(AutoLockOptions::default())
+        );
+        assert_eq!(parse("auto_lock = { idle = '5m', other = '1h' }"), None);
+    }
+
+    use super::duration;
     use serde::de;
     use serde::Deserialize;
     use serde::Deserializer;
     use std::fmt;
     use std::fmt::Formatter;
     use std::time::Duration;
+    use std::time::Instant;
+}
+
+pub(crate) use duration::parse as parse_duration;
+mod duration {
+    /// Parse a duration like `30s`, `12m`, `1h` or `7d`.
+    pub(crate) fn parse(s: &str) -> Option<Duration> {
+        let s = s.trim();
+
+        let multiplier = match s.chars().last()? {
+            'd' => 24 * 60 * 60,
+            'h' => 60 * 60,
+            'm' => 60,
+            's' => 1,
+            _ => return None,
+        };
+
+        let number = s[..s.len() - 1].parse::<u64>().ok()?;
+
+        let seconds = number.checked_mul(multiplier)?;
+
+        Some(Duration::from_secs(seconds))
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Duration, D::Error> {
+        deserializer.deserialize_str(Visitor)
+    }
+
+    pub(crate) fn deserialize_option<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        deserialize(deserializer).map(Some)
+    }
+
+    struct Visitor;
+    impl<'de> de::Visitor<'de> for Visitor {
+        type Value = Duration;
+        fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            f.write_str("a duration")
+        }
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+            parse(v).ok_or_else(|| de::Error::invalid_value(de::Unexpected::Str(v), &self))
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("7d"), Some(Duration::from_secs(7 * 24 * 60 * 60)));
+        assert_eq!(parse("d"), None);
+        assert_eq!(parse("3w"), None);
+    }
+
+    use serde::de;
+    use serde::Deserializer;
+    use std::fmt;
+    use std::fmt::Formatter;
+    use std::time::Duration;
+}
+
+fn seven_days() -> Duration {
+    Duration::from_secs(7 * 24 * 60 * 60)
 }
 
 fn desktop_string() -> String {
@@ -329,6 +743,11 @@ mod serde_default {
 
 use crate::auth;
 use anyhow::Context as _;
+use rofi_bw_common::default_copy::DefaultCopy;
+use rofi_bw_common::ipc;
+use rofi_bw_common::menu_keybinds;
+use rofi_bw_common::usage::Sort;
 use rofi_bw_util::fs;
 use serde::Deserialize;
 use std::io;
+use std::time::Duration;
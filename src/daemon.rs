@@ -1,19 +1,88 @@
 #[derive(bincode::Encode, bincode::Decode)]
 pub(crate) enum Request {
     ShowMenu(ShowMenu),
+    Send(send::Request),
+    Get(get::Request),
+    List(list::Request),
+    Sync,
+    /// Answered by the daemon’s background thread, even while the menu is open.
+    Status {
+        json: bool,
+    },
+    Lock,
     Quit,
 }
 
+impl Request {
+    /// Whether the daemon answers the request with a [`Reply`], rather than just ending the
+    /// connection.
+    const fn has_reply(&self) -> bool {
+        match self {
+            Self::Get(_) | Self::List(_) | Self::Sync | Self::Status { .. } => true,
+            Self::ShowMenu(_) | Self::Send(_) | Self::Lock | Self::Quit => false,
+        }
+    }
+}
+
 #[derive(Clone, bincode::Encode, bincode::Decode)]
 pub(crate) struct ShowMenu {
-    /// The value of the `$DISPLAY` environment variable.
-    pub(crate) display: String,
+    /// The display to show the menu on.
+    pub(crate) display: Display,
 
     /// The initial filter to start Rofi with.
     pub(crate) filter: String,
 
     /// The view to display in `rofi-bw`; `None` if it will just show the last in history
     pub(crate) view: Option<ipc::View>,
+
+    /// Whether to show the logins matching the URI of the focused window instead of `view`.
+    pub(crate) from_active_window: bool,
+}
+
+/// The X11 display and Wayland compositor of the invoking process; at least one is set.
+#[derive(Clone, bincode::Encode, bincode::Decode)]
+pub(crate) struct Display {
+    /// The value of the `$DISPLAY` environment variable.
+    pub(crate) x11: Option<String>,
+    /// The value of the `$WAYLAND_DISPLAY` environment variable.
+    pub(crate) wayland: Option<String>,
+}
+
+impl Display {
+    pub(crate) fn from_env() -> anyhow::Result<Self> {
+        let var = |name| env::var(name).ok().filter(|value| !value.is_empty());
+        let display = Self {
+            x11: var("DISPLAY"),
+            wayland: var("WAYLAND_DISPLAY"),
+        };
+        anyhow::ensure!(
+            display.x11.is_some() || display.wayland.is_some(),
+            "neither `$DISPLAY` nor `$WAYLAND_DISPLAY` is set"
+        );
+        Ok(display)
+    }
+
+    /// Set `$DISPLAY` and `$WAYLAND_DISPLAY` for the command to those of this display.
+    pub(crate) fn apply_to_env(&self, command: &mut process::Command) {
+        for (name, value) in [("DISPLAY", &self.x11), ("WAYLAND_DISPLAY", &self.wayland)] {
+            match value {
+                Some(value) => command.env(name, value),
+                None => command.env_remove(name),
+            };
+        }
+    }
+
+    /// Make rofi open on this display.
+    pub(crate) fn apply_to_rofi(&self, rofi: &mut process::Command) {
+        if let Some(x11) = &self.x11 {
+            rofi.arg("-display").arg(x11);
+        }
+        // rofi-wayland uses Wayland if it can, so this must be cleared if rofi is opened from X11.
+        match &self.wayland {
+            Some(wayland) => rofi.env("WAYLAND_DISPLAY", wayland),
+            None => rofi.env_remove("WAYLAND_DISPLAY"),
+        };
+    }
 }
 
 #[derive(bincode::Encode, bincode::Decode)]
@@ -22,20 +91,30 @@ enum Response {
     Busy,
 }
 
-pub(crate) fn invoke(runtime_dir: &fs::Path, request: &Request) -> anyhow::Result<bool> {
+/// Sent after `Response::Ok` to requests that produce output, once the daemon has handled them.
+#[derive(bincode::Encode, bincode::Decode)]
+enum Reply {
+    Output(String),
+    Error(String),
+}
+
+/// Send a request to the running daemon, returning its output (empty for requests that don’t
+/// produce any), or `None` if there is no daemon running.
+pub(crate) fn invoke(runtime_dir: &fs::Path, request: &Request) -> anyhow::Result<Option<String>> {
     invoke_inner(runtime_dir, request).context("failed to invoke daemon")
 }
 
-fn invoke_inner(runtime_dir: &fs::Path, request: &Request) -> anyhow::Result<bool> {
+fn invoke_inner(runtime_dir: &fs::Path, request: &Request) -> anyhow::Result<Option<String>> {
     let socket_path = runtime_dir.join(socket_file_name());
 
     let acceptable_errors = [io::ErrorKind::NotFound, io::ErrorKind::ConnectionRefused];
     let mut socket = match UnixStream::connect(socket_path) {
         Ok(socket) => socket,
-        Err(e) if acceptable_errors.contains(&e.kind()) => return Ok(false),
+        Err(e) if acceptable_errors.contains(&e.kind()) => return Ok(None),
         Err(e) => return Err(e).context("failed to connect to client socket"),
     };
 
+    let request_has_reply = request.has_reply();
     let request = bincode::encode_to_vec(request, bincode_config()).unwrap();
 
     socket
@@ -53,35 +132,101 @@ fn invoke_inner(runtime_dir: &fs::Path, request: &Request) -> anyhow::Result<boo
         .read_to_end(&mut response)
         .context("failed to read from daemon")?;
 
-    let (response, _) = bincode::decode_from_slice(&response, bincode_config())
+    let (decoded, len) = bincode::decode_from_slice(&response, bincode_config())
         .context("failed to decode daemon response")?;
 
-    match response {
+    match decoded {
         Response::Ok => {}
         Response::Busy => anyhow::bail!("menu is already open"),
     }
 
-    Ok(true)
+    let rest = &response[len..];
+    if rest.is_empty() {
+        // The daemon ended the connection without answering, e.g. because it exited.
+        anyhow::ensure!(!request_has_reply, "daemon did not reply");
+        return Ok(Some(String::new()));
+    }
+
+    let (reply, _) = bincode::decode_from_slice(rest, bincode_config())
+        .context("failed to decode daemon reply")?;
+
+    match reply {
+        Reply::Output(output) => Ok(Some(output)),
+        Reply::Error(message) => Err(anyhow!(message)),
+    }
+}
+
+/// The connection a request came in on, used to reply to it.
+///
+/// Dropping it without replying ends the connection, which is all requests that don’t produce output
+/// need.
+pub(crate) struct Replier(Option<UnixStream>);
+
+impl Replier {
+    pub(crate) fn reply(self, result: anyhow::Result<String>) {
+        let reply = match result {
+            Ok(output) => Reply::Output(output),
+            Err(e) => Reply::Error(format!("{e:#}")),
+        };
+        if let Some(mut connection) = self.0 {
+            let buf = bincode::encode_to_vec(reply, bincode_config()).unwrap();
+            if let Err(e) = connection.write_all(&buf) {
+                eprintln!("Warning: failed to reply to request: {e}");
+            }
+        }
+    }
 }
 
 pub(crate) struct Daemon {
     shared: Arc<Shared>,
-    auto_lock: AutoLock,
+    auto_lock: AutoLockOptions,
 }
 
 struct Shared {
     state: Mutex<State>,
     transfer_start: Condvar,
+    status: Mutex<StatusInfo>,
+    /// Set by a `Locker`; only changed while `state` is locked, so that `wait` can’t miss it.
+    lock_requested: AtomicBool,
+    /// The process ID of rofi while the menu is open, so that a `Locker` can close it.
+    menu: Mutex<Option<u32>>,
+}
+
+/// What `rofi-bw status` reports, kept up to date by the main thread.
+#[derive(Default)]
+struct StatusInfo {
+    session: Option<SessionInfo>,
+    /// When the vault will lock for being idle; `None` while the menu is open.
+    lock_deadline: Option<Instant>,
+}
+
+#[derive(Clone)]
+struct SessionInfo {
+    email: String,
+    synced_at: SystemTime,
+    /// When the vault will lock for having been unlocked for too long.
+    max_deadline: Option<Instant>,
+}
+
+impl StatusInfo {
+    /// When the vault will next lock, whichever the reason.
+    fn deadline(&self) -> Option<Instant> {
+        let max_deadline = self
+            .session
+            .as_ref()
+            .and_then(|session| session.max_deadline);
+        self.lock_deadline.into_iter().chain(max_deadline).min()
+    }
 }
 
 enum State {
     ShowingMenu,
     Waiting,
-    Transferring(Request),
+    Transferring(Request, UnixStream),
 }
 
 impl Daemon {
-    pub(crate) fn bind(runtime_dir: &fs::Path, auto_lock: AutoLock) -> anyhow::Result<Self> {
+    pub(crate) fn bind(runtime_dir: &fs::Path, auto_lock: AutoLockOptions) -> anyhow::Result<Self> {
         let socket_path = runtime_dir.join(socket_file_name());
 
         drop(fs::create_dir_all(runtime_dir));
@@ -93,6 +238,9 @@ impl Daemon {
         let shared = Arc::new(Shared {
             state: Mutex::new(State::ShowingMenu),
             transfer_start: Condvar::new(),
+            status: Mutex::new(StatusInfo::default()),
+            lock_requested: AtomicBool::new(false),
+            menu: Mutex::new(None),
         });
 
         thread::Builder::new()
@@ -105,33 +253,80 @@ impl Daemon {
         Ok(Self { shared, auto_lock })
     }
 
-    pub(crate) fn wait(&mut self) -> Request {
-        if self.auto_lock == AutoLock::After(Duration::ZERO) {
-            return Request::Quit;
+    /// Record the current session, for `rofi-bw status` and so that `Locker`s know whether there is
+    /// anything to lock.
+    pub(crate) fn set_session(&self, session: Option<&Session<'_, '_>>) {
+        let state = self.shared.state.lock().unwrap();
+        self.shared.status.lock().unwrap().session = session.map(|session| SessionInfo {
+            email: session.email().to_owned(),
+            synced_at: session.synced_at(),
+            max_deadline: match self.auto_lock.max {
+                AutoLock::Never => None,
+                AutoLock::After(max) => Some(session.unlocked_at() + max),
+            },
+        });
+        if session.is_none() {
+            self.shared
+                .lock_requested
+                .store(false, atomic::Ordering::Relaxed);
+        }
+        drop(state);
+    }
+
+    /// Get a handle that can lock the vault from other threads.
+    pub(crate) fn locker(&self) -> Locker {
+        Locker(self.shared.clone())
+    }
+
+    /// Wait for the next request.
+    ///
+    /// The idle countdown starts with the first call after the menu was last shown, and isn’t
+    /// reset by requests that don’t show it.
+    pub(crate) fn wait(&mut self) -> (Request, Replier) {
+        if self.auto_lock.idle == AutoLock::After(Duration::ZERO) {
+            return (Request::Quit, Replier(None));
         }
 
+        let deadline = {
+            let mut status = self.shared.status.lock().unwrap();
+            if let AutoLock::After(timeout) = self.auto_lock.idle {
+                status
+                    .lock_deadline
+                    .get_or_insert_with(|| Instant::now() + timeout);
+            }
+            status.deadline()
+        };
+
         let mut state = self.shared.state.lock().unwrap();
         match *state {
-            State::Waiting | State::Transferring(_) => unreachable!(),
+            State::Waiting | State::Transferring(..) => unreachable!(),
             State::ShowingMenu => {}
         }
+
+        // Locking may have been requested while the menu was open.
+        let lock_requested = &self.shared.lock_requested;
+        if lock_requested.swap(false, atomic::Ordering::Relaxed) {
+            return (Request::Lock, Replier(None));
+        }
+
         *state = State::Waiting;
 
         let condition = |state: &mut State| match *state {
             State::ShowingMenu => unreachable!(),
-            State::Waiting => true,
-            State::Transferring(_) => false,
+            State::Waiting => !lock_requested.load(atomic::Ordering::Relaxed),
+            State::Transferring(..) => false,
         };
 
-        match self.auto_lock {
-            AutoLock::Never => {
+        match deadline {
+            None => {
                 state = self
                     .shared
                     .transfer_start
                     .wait_while(state, condition)
                     .unwrap();
             }
-            AutoLock::After(timeout) => {
+            Some(deadline) => {
+                let timeout = deadline.saturating_duration_since(Instant::now());
                 let (new_state, res) = self
                     .shared
                     .transfer_start
@@ -139,15 +334,62 @@ impl Daemon {
                     .unwrap();
                 state = new_state;
                 if res.timed_out() {
-                    return Request::Quit;
+                    return (Request::Quit, Replier(None));
                 }
             }
         }
 
-        match mem::replace(&mut *state, State::ShowingMenu) {
-            State::Transferring(request) => request,
-            _ => unreachable!(),
+        let State::Transferring(request, connection) =
+            mem::replace(&mut *state, State::ShowingMenu)
+        else {
+            lock_requested.store(false, atomic::Ordering::Relaxed);
+            return (Request::Lock, Replier(None));
+        };
+        drop(state);
+
+        if let Request::ShowMenu(_) | Request::Send(_) = request {
+            self.shared.status.lock().unwrap().lock_deadline = None;
+        }
+
+        (request, Replier(Some(connection)))
+    }
+}
+
+/// Locks the vault from outside the main thread, for `lock_on` events.
+#[derive(Clone)]
+pub(crate) struct Locker(Arc<Shared>);
+
+impl Locker {
+    /// Lock the vault straight away if the main thread is waiting, or otherwise close the menu and
+    /// lock it once the main thread is waiting again. Does nothing if the vault isn’t unlocked.
+    pub(crate) fn lock(&self) {
+        let state = self.0.state.lock().unwrap();
+        if self.0.status.lock().unwrap().session.is_none() {
+            return;
         }
+        self.0.lock_requested.store(true, atomic::Ordering::Relaxed);
+        drop(state);
+        self.0.transfer_start.notify_one();
+
+        if let Some(pid) = *self.0.menu.lock().unwrap() {
+            let pid = libc::pid_t::try_from(pid).unwrap();
+            unsafe { libc::kill(pid, libc::SIGTERM) };
+        }
+    }
+
+    /// Record that the menu is open in the given rofi process, so that locking closes it.
+    pub(crate) fn menu_opened(&self, pid: u32) {
+        *self.0.menu.lock().unwrap() = Some(pid);
+    }
+
+    /// Record that the menu has closed, returning whether it was closed to lock the vault.
+    ///
+    /// This must be called before the rofi process is reaped, so that its process ID can’t have
+    /// been reused by the time it is killed.
+    pub(crate) fn menu_closed(&self) -> bool {
+        let mut menu = self.0.menu.lock().unwrap();
+        *menu = None;
+        self.0.lock_requested.load(atomic::Ordering::Relaxed)
     }
 }
 
@@ -184,25 +426,63 @@ fn handle_connection_inner(shared: Arc<Shared>, mut connection: UnixStream) -> O
     connection.read_to_end(&mut buf).ok()?;
     let (request, _) = bincode::decode_from_slice(&buf, bincode_config()).ok()?;
 
-    let response = {
-        let mut state = shared.state.lock().unwrap();
-        match *state {
-            State::Waiting => {
-                *state = State::Transferring(request);
-                shared.transfer_start.notify_one();
-                Response::Ok
-            }
-            State::ShowingMenu | State::Transferring(_) => Response::Busy,
+    let mut state = shared.state.lock().unwrap();
+    let waiting = match *state {
+        State::Waiting => true,
+        State::ShowingMenu | State::Transferring(..) => false,
+    };
+
+    if let Request::Status { json } = request {
+        drop(state);
+        let status = status(&shared, !waiting).format(json);
+        for message in [
+            bincode::encode_to_vec(Response::Ok, bincode_config()).unwrap(),
+            bincode::encode_to_vec(Reply::Output(status), bincode_config()).unwrap(),
+        ] {
+            connection.write_all(&message).ok()?;
         }
+        return Some(());
+    }
+
+    let response = if waiting {
+        Response::Ok
+    } else {
+        Response::Busy
     };
 
+    // The response is written before handing over the connection, so that it always comes before
+    // the reply.
     buf.clear();
     bincode::encode_into_std_write(response, &mut buf, bincode_config()).unwrap();
     connection.write_all(&buf).ok()?;
 
+    if waiting {
+        *state = State::Transferring(request, connection);
+        shared.transfer_start.notify_one();
+    }
+
     Some(())
 }
 
+fn status(shared: &Shared, busy: bool) -> Status {
+    let status = shared.status.lock().unwrap();
+    let session = status.session.clone();
+    Status {
+        running: true,
+        unlocked: session.is_some(),
+        email: session.as_ref().map(|session| session.email.clone()),
+        busy,
+        auto_lock_seconds: status
+            .deadline()
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()).as_secs()),
+        last_sync: session.map(|session| {
+            OffsetDateTime::from(session.synced_at)
+                .format(&Rfc3339)
+                .unwrap()
+        }),
+    }
+}
+
 fn socket_file_name() -> String {
     let argv0 = env::args_os().next();
     let base = argv0
@@ -216,7 +496,53 @@ fn bincode_config() -> impl bincode::config::Config {
     bincode::config::standard()
 }
 
+#[cfg(test)]
+fn unlocked_daemon(name: &str) -> Daemon {
+    let runtime_dir = env::temp_dir().join(format!("rofi-bw-test-{}-{name}", process::id()));
+    let auto_lock = AutoLockOptions {
+        idle: AutoLock::Never,
+        max: AutoLock::Never,
+    };
+    let daemon = Daemon::bind(&runtime_dir, auto_lock).unwrap();
+    daemon.shared.status.lock().unwrap().session = Some(SessionInfo {
+        email: String::new(),
+        synced_at: SystemTime::now(),
+        max_deadline: None,
+    });
+    daemon
+}
+
+#[test]
+fn lock_while_waiting() {
+    let mut daemon = unlocked_daemon("waiting");
+    let locker = daemon.locker();
+    let locking = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(100));
+        locker.lock();
+    });
+    assert!(matches!(daemon.wait(), (Request::Lock, _)));
+    locking.join().unwrap();
+}
+
+#[test]
+fn lock_while_showing_menu() {
+    let mut daemon = unlocked_daemon("menu");
+    let locker = daemon.locker();
+    let mut menu = process::Command::new("sleep").arg("60").spawn().unwrap();
+    locker.menu_opened(menu.id());
+    locker.lock();
+    assert!(locker.menu_closed());
+    assert!(!menu.wait().unwrap().success());
+    assert!(matches!(daemon.wait(), (Request::Lock, _)));
+}
+
 use crate::config::AutoLock;
+use crate::config::AutoLockOptions;
+use crate::get;
+use crate::list;
+use crate::send;
+use crate::status::Status;
+use crate::Session;
 use anyhow::anyhow;
 use anyhow::Context as _;
 use rofi_bw_common::ipc;
@@ -230,8 +556,15 @@ use std::mem;
 use std::net;
 use std::os::unix::net::UnixListener;
 use std::os::unix::net::UnixStream;
+use std::process;
+use std::sync::atomic;
+use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 use std::sync::Condvar;
 use std::sync::Mutex;
 use std::thread;
 use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
@@ -0,0 +1,290 @@
+//! Reading single values out of the vault without the menu, for `rofi-bw get` and
+//! `rofi-bw git-credential`.
+
+#[derive(bincode::Encode, bincode::Decode)]
+pub(crate) struct Request {
+    pub(crate) item: Item,
+    /// Only consider logins with this username.
+    pub(crate) username: Option<String>,
+    pub(crate) output: Output,
+}
+
+#[derive(bincode::Encode, bincode::Decode)]
+pub(crate) enum Item {
+    Uuid([u8; 16]),
+    /// Must be an exact match.
+    Name(String),
+    /// Matches logins with a URI that matches this one according to its match detection setting.
+    Uri(String),
+}
+
+#[derive(bincode::Encode, bincode::Decode)]
+pub(crate) enum Output {
+    Field(Field),
+    /// The username and password, in the format of Git’s credential helper protocol.
+    GitCredential,
+}
+
+#[derive(bincode::Encode, bincode::Decode)]
+pub(crate) enum Field {
+    Password,
+    Username,
+    Totp,
+    Custom(String),
+}
+
+impl Display for Field {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Password => f.write_str("password"),
+            Self::Username => f.write_str("username"),
+            Self::Totp => f.write_str("TOTP code"),
+            Self::Custom(name) => write!(f, "field “{name}”"),
+        }
+    }
+}
+
+pub(crate) struct Found {
+    /// The name of the item the value is from.
+    pub(crate) name: String,
+    pub(crate) reprompt: bool,
+    /// What is being accessed, for confirmation prompts.
+    pub(crate) what: String,
+    /// The text to print.
+    pub(crate) output: Zeroizing<String>,
+}
+
+/// Find the requested value. The caller is responsible for confirming access to it.
+pub(crate) fn find(session: &Session<'_, '_>, request: &Request) -> anyhow::Result<Found> {
+    find_in(&Vault::load(session)?, request)
+}
+
+fn find_in(vault: &Vault, request: &Request) -> anyhow::Result<Found> {
+    let key = &vault.key;
+
+    let page = match &request.item {
+        Item::Uri(uri) => Some(uri_match::Page::new(uri.clone(), &vault.domains)),
+        Item::Uuid(_) | Item::Name(_) => None,
+    };
+
+    let decrypts_to = |cipher_string: &CipherString<String>, expected: &str| {
+        cipher_string
+            .decrypt(key)
+            .is_ok_and(|value| value == expected)
+    };
+
+    // Logins with URIs that use the default match detection are matched by their host before
+    // their domain, so that a login for another host on the same domain is only used if no login
+    // is for this host.
+    let find_matches = |default_match_type: UriMatchType| {
+        let mut matches = Vec::new();
+        for cipher in &vault.ciphers {
+            let is_match = match &request.item {
+                Item::Uuid(id) => cipher.id == Uuid::from_bytes(*id),
+                Item::Name(name) => decrypts_to(&cipher.name, name),
+                Item::Uri(_) => {
+                    let page = page.as_ref().unwrap();
+                    cipher
+                        .login
+                        .iter()
+                        .flat_map(|login| login.uris.iter().flatten())
+                        .any(|uri| {
+                            let decrypted = uri.uri.as_ref().and_then(|uri| uri.decrypt(key).ok());
+                            let match_type = uri.match_type.unwrap_or(default_match_type);
+                            decrypted
+                                .is_some_and(|decrypted| page.matches(&decrypted, Some(match_type)))
+                        })
+                }
+            };
+            if !is_match {
+                continue;
+            }
+
+            if let Some(username) = &request.username {
+                let login_username = cipher.login.as_ref().and_then(|l| l.username.as_ref());
+                if !login_username.is_some_and(|u| decrypts_to(u, username)) {
+                    continue;
+                }
+            }
+
+            matches.push(cipher);
+        }
+        matches
+    };
+
+    let mut matches = find_matches(UriMatchType::Host);
+    if matches.is_empty() && page.is_some() {
+        matches = find_matches(UriMatchType::Domain);
+    }
+
+    let cipher = match *matches {
+        [cipher] => cipher,
+        [] => anyhow::bail!("no matching item found"),
+        [..] => {
+            let mut names = String::new();
+            for cipher in &matches {
+                if !names.is_empty() {
+                    names.push_str(", ");
+                }
+                names.push_str(&cipher.name.decrypt(key)?);
+            }
+            anyhow::bail!("multiple items match: {names}");
+        }
+    };
+
+    let name = cipher.name.decrypt(key)?;
+
+    let (what, output) = match &request.output {
+        Output::Field(field) => {
+            let mut value = field_value(cipher, field, key)?
+                .with_context(|| format!("{name} has no {field}"))?;
+            value.push('\n');
+            (field.to_string(), value)
+        }
+        Output::GitCredential => {
+            let login = cipher.login.as_ref().context("item is not a login")?;
+            let mut output = Zeroizing::new(String::new());
+            if let Some(username) = &login.username {
+                writeln!(
+                    output,
+                    "username={}",
+                    *Zeroizing::new(username.decrypt(key)?)
+                )
+                .unwrap();
+            }
+            if let Some(password) = &login.password {
+                writeln!(
+                    output,
+                    "password={}",
+                    *Zeroizing::new(password.decrypt(key)?)
+                )
+                .unwrap();
+            }
+            ("username and password".to_owned(), output)
+        }
+    };
+
+    Ok(Found {
+        name,
+        reprompt: cipher.reprompt(),
+        what,
+        output,
+    })
+}
+
+fn field_value(
+    cipher: &vault::Cipher,
+    field: &Field,
+    key: &SymmetricKey,
+) -> anyhow::Result<Option<Zeroizing<String>>> {
+    let login = cipher.login.as_ref();
+    let value = match field {
+        Field::Password => login.and_then(|login| login.password.as_ref()),
+        Field::Username => login.and_then(|login| login.username.as_ref()),
+        Field::Totp => {
+            let totp = match login.and_then(|login| login.totp.as_ref()) {
+                Some(totp) => Zeroizing::new(totp.decrypt(key)?),
+                None => return Ok(None),
+            };
+            return Ok(Some(Zeroizing::new(totp::generate(
+                &totp,
+                SystemTime::now(),
+            )?)));
+        }
+        Field::Custom(name) => {
+            let mut value = None;
+            for field in cipher.fields.iter().flatten() {
+                let field_name = field.name.as_ref().map(|n| n.decrypt(key)).transpose()?;
+                if field_name.as_deref() == Some(&**name) {
+                    value = field.value.as_ref();
+                    break;
+                }
+            }
+            value
+        }
+    };
+
+    Ok(match value {
+        Some(value) => Some(Zeroizing::new(value.decrypt(key)?)),
+        None => None,
+    })
+}
+
+#[cfg(test)]
+fn find_output(item: Item, username: Option<&str>, output: Output) -> anyhow::Result<String> {
+    let request = Request {
+        item,
+        username: username.map(str::to_owned),
+        output,
+    };
+    let found = find_in(&vault::test_vault(), &request)?;
+    Ok(found.output.to_string())
+}
+
+#[test]
+fn find_by_uri() {
+    let password = || Output::Field(Field::Password);
+    let uri = |uri: &str| Item::Uri(uri.to_owned());
+
+    let exact = find_output(uri("https://github.com/settings"), None, password());
+    assert_eq!(exact.unwrap(), "GitHub password\n");
+    // Both logins are on the domain, but only one is for this host.
+    let host = find_output(uri("https://gist.github.com/alice"), None, password());
+    assert_eq!(host.unwrap(), "GitHub Gist password\n");
+
+    let domain = find_output(uri("https://docs.github.com"), None, password());
+    assert!(domain
+        .unwrap_err()
+        .to_string()
+        .contains("multiple items match"));
+    let username = find_output(uri("https://docs.github.com"), Some("bob"), password());
+    assert_eq!(username.unwrap(), "GitHub Gist password\n");
+
+    let none = find_output(uri("https://gitlab.com"), None, password());
+    assert!(none.is_err());
+}
+
+#[test]
+fn find_fields() {
+    let name = |name: &str| Item::Name(name.to_owned());
+
+    let credential = find_output(name("GitHub"), None, Output::GitCredential).unwrap();
+    assert_eq!(credential, "username=alice\npassword=GitHub password\n");
+
+    let uuid = Uuid::parse_str("00000000-0000-0000-0000-000000000002").unwrap();
+    let username = find_output(
+        Item::Uuid(*uuid.as_bytes()),
+        None,
+        Output::Field(Field::Username),
+    );
+    assert_eq!(username.unwrap(), "bob\n");
+
+    let request = Request {
+        item: name("Bank"),
+        username: None,
+        output: Output::Field(Field::Custom("PIN".to_owned())),
+    };
+    let found = find_in(&vault::test_vault(), &request).unwrap();
+    assert!(found.reprompt);
+    assert_eq!(*found.output, "1234\n");
+
+    assert!(find_output(name("Old GitHub"), None, Output::GitCredential).is_err());
+    assert!(find_output(name("Recipe"), None, Output::GitCredential).is_err());
+}
+
+use crate::vault;
+use crate::vault::Vault;
+use crate::Session;
+use anyhow::Context as _;
+use rofi_bw_common::totp;
+use rofi_bw_common::uri_match;
+use rofi_bw_common::uri_match::UriMatchType;
+use rofi_bw_common::CipherString;
+use rofi_bw_common::SymmetricKey;
+use std::fmt;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Write as _;
+use std::time::SystemTime;
+use uuid::Uuid;
+use zeroize::Zeroizing;
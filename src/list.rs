@@ -0,0 +1,321 @@
+//! Listing and searching the vault without the menu, for `rofi-bw list`.
+
+#[derive(bincode::Encode, bincode::Decode)]
+pub(crate) struct Request {
+    /// The exact name of the folder to list; `Some("")` lists items in no folder.
+    pub(crate) folder: Option<String>,
+    pub(crate) cipher_type: Option<CipherType>,
+    /// Only list items whose name, username or URIs contain this, case-insensitively.
+    pub(crate) search: Option<String>,
+    pub(crate) include_secrets: bool,
+    pub(crate) json: bool,
+}
+
+pub(crate) struct Listed {
+    /// How many items were listed.
+    pub(crate) count: usize,
+    /// The text to print.
+    pub(crate) output: Zeroizing<String>,
+}
+
+#[derive(Serialize)]
+struct Item {
+    id: Uuid,
+    name: String,
+    #[serde(rename = "type")]
+    cipher_type: &'static str,
+    folder: Option<String>,
+    favorite: bool,
+    reprompt: bool,
+    username: Option<String>,
+    uris: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    secrets: Option<Secrets>,
+}
+
+/// Left out of items that require the master password to be entered again.
+#[derive(Serialize)]
+struct Secrets {
+    password: Option<String>,
+    totp: Option<String>,
+    notes: Option<String>,
+    fields: Vec<Field>,
+}
+
+#[derive(Serialize)]
+struct Field {
+    name: Option<String>,
+    value: Option<String>,
+}
+
+impl Drop for Item {
+    fn drop(&mut self) {
+        self.name.zeroize();
+        self.username.zeroize();
+        self.uris.zeroize();
+        if let Some(secrets) = &mut self.secrets {
+            secrets.password.zeroize();
+            secrets.totp.zeroize();
+            secrets.notes.zeroize();
+            for field in &mut secrets.fields {
+                field.name.zeroize();
+                field.value.zeroize();
+            }
+        }
+    }
+}
+
+/// List the items matching the request. The caller is responsible for confirming access to secrets.
+pub(crate) fn list(session: &Session<'_, '_>, request: &Request) -> anyhow::Result<Listed> {
+    list_vault(&Vault::load(session)?, request)
+}
+
+fn list_vault(vault: &Vault, request: &Request) -> anyhow::Result<Listed> {
+    let key = &vault.key;
+
+    let mut folders = HashMap::new();
+    for folder in &vault.folders {
+        match folder.name.decrypt(key) {
+            Ok(name) => {
+                folders.insert(folder.id, name);
+            }
+            Err(e) => {
+                let e = anyhow!(e).context(format!("skipping folder {}", folder.id));
+                eprintln!("Warning: {e:?}");
+            }
+        }
+    }
+
+    if let Some(folder) = &request.folder {
+        anyhow::ensure!(
+            folder.is_empty() || folders.values().any(|name| name == folder),
+            "no folder named {folder}"
+        );
+    }
+
+    let search = request.search.as_deref().map(str::to_lowercase);
+
+    let mut items = Vec::new();
+    for cipher in &vault.ciphers {
+        let Some(cipher_type) = cipher.cipher_type() else {
+            continue;
+        };
+        if request.cipher_type.is_some_and(|t| t != cipher_type) {
+            continue;
+        }
+
+        let folder = cipher.folder_id.and_then(|id| folders.get(&id)).cloned();
+        if let Some(wanted) = &request.folder {
+            if folder.as_deref().unwrap_or_default() != wanted {
+                continue;
+            }
+        }
+
+        match list_item(cipher, cipher_type, folder, key, search.as_deref(), request) {
+            Ok(Some(item)) => items.push(item),
+            Ok(None) => {}
+            Err(e) => {
+                let e = e.context(format!("skipping item {}", cipher.id));
+                eprintln!("Warning: {e:?}");
+            }
+        }
+    }
+
+    items.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut output = Zeroizing::new(if request.json {
+        serde_json::to_string_pretty(&items).unwrap()
+    } else {
+        let mut output = String::new();
+        for item in &items {
+            writeln!(output, "{}\t{}", item.id, item.name).unwrap();
+        }
+        output
+    });
+    if request.json {
+        output.push('\n');
+    }
+
+    Ok(Listed {
+        count: items.len(),
+        output,
+    })
+}
+
+/// Decrypt an item, returning `None` if it doesn’t match the search.
+fn list_item(
+    cipher: &Cipher,
+    cipher_type: CipherType,
+    folder: Option<String>,
+    key: &SymmetricKey,
+    search: Option<&str>,
+    request: &Request,
+) -> anyhow::Result<Option<Item>> {
+    let decrypt = |cipher_string: Option<&CipherString<String>>| {
+        cipher_string
+            .map(|cipher_string| cipher_string.decrypt(key))
+            .transpose()
+    };
+
+    let login = cipher.login.as_ref();
+    let mut item = Item {
+        id: cipher.id,
+        name: cipher.name.decrypt(key)?,
+        cipher_type: type_name(cipher_type),
+        folder,
+        favorite: cipher.favorite,
+        reprompt: cipher.reprompt(),
+        username: decrypt(login.and_then(|login| login.username.as_ref()))?,
+        uris: Vec::new(),
+        secrets: None,
+    };
+    for uri in login
+        .and_then(|login| login.uris.as_ref())
+        .into_iter()
+        .flatten()
+    {
+        item.uris.extend(decrypt(uri.uri.as_ref())?);
+    }
+
+    if let Some(search) = search {
+        let matches = |s: &str| s.to_lowercase().contains(search);
+        if !(matches(&item.name)
+            || item.username.as_deref().is_some_and(matches)
+            || item.uris.iter().any(|uri| matches(uri)))
+        {
+            return Ok(None);
+        }
+    }
+
+    if request.include_secrets && !item.reprompt {
+        let mut fields = Vec::new();
+        for field in cipher.fields.iter().flatten() {
+            fields.push(Field {
+                name: decrypt(field.name.as_ref())?,
+                value: decrypt(field.value.as_ref())?,
+            });
+        }
+        item.secrets = Some(Secrets {
+            password: decrypt(login.and_then(|login| login.password.as_ref()))?,
+            totp: decrypt(login.and_then(|login| login.totp.as_ref()))?,
+            notes: decrypt(cipher.notes.as_ref())?,
+            fields,
+        });
+    }
+
+    Ok(Some(item))
+}
+
+fn type_name(cipher_type: CipherType) -> &'static str {
+    match cipher_type {
+        CipherType::Login => "login",
+        CipherType::SecureNote => "secure-note",
+        CipherType::Card => "card",
+        CipherType::Identity => "identity",
+        CipherType::SshKey => "ssh-key",
+    }
+}
+
+#[cfg(test)]
+fn list_names(request: &Request) -> anyhow::Result<Vec<String>> {
+    let listed = list_vault(&crate::vault::test_vault(), request)?;
+    let items = serde_json::from_str::<Vec<serde_json::Value>>(&listed.output).unwrap();
+    assert_eq!(items.len(), listed.count);
+    Ok(items
+        .iter()
+        .map(|item| item["name"].as_str().unwrap().to_owned())
+        .collect())
+}
+
+#[cfg(test)]
+fn request() -> Request {
+    Request {
+        folder: None,
+        cipher_type: None,
+        search: None,
+        include_secrets: false,
+        json: true,
+    }
+}
+
+#[test]
+fn filters() {
+    let all = list_names(&request()).unwrap();
+    assert_eq!(all, ["Bank", "GitHub", "GitHub Gist", "Recipe"]);
+
+    let no_folder = list_names(&Request {
+        folder: Some(String::new()),
+        ..request()
+    });
+    assert_eq!(no_folder.unwrap(), ["Bank", "GitHub Gist", "Recipe"]);
+
+    let work = list_names(&Request {
+        folder: Some("Work".to_owned()),
+        ..request()
+    });
+    assert_eq!(work.unwrap(), ["GitHub"]);
+
+    let missing = list_names(&Request {
+        folder: Some("Home".to_owned()),
+        ..request()
+    });
+    assert!(missing.is_err());
+
+    let notes = list_names(&Request {
+        cipher_type: Some(CipherType::SecureNote),
+        ..request()
+    });
+    assert_eq!(notes.unwrap(), ["Recipe"]);
+
+    for (search, expected) in [
+        ("GITHUB", &["GitHub", "GitHub Gist"][..]),
+        ("bob", &["GitHub Gist"]),
+        ("bank.example", &["Bank"]),
+    ] {
+        let found = list_names(&Request {
+            search: Some(search.to_owned()),
+            ..request()
+        });
+        assert_eq!(found.unwrap(), expected);
+    }
+}
+
+#[test]
+fn secrets() {
+    let listed = list_vault(
+        &crate::vault::test_vault(),
+        &Request {
+            include_secrets: true,
+            ..request()
+        },
+    )
+    .unwrap();
+    let items = serde_json::from_str::<serde_json::Value>(&listed.output).unwrap();
+
+    let bank = &items[0];
+    assert_eq!(bank["reprompt"], true);
+    assert_eq!(bank["username"], "carol");
+    assert!(bank.get("secrets").is_none());
+
+    let github = &items[1];
+    assert_eq!(github["folder"], "Work");
+    assert_eq!(github["secrets"]["password"], "GitHub password");
+
+    let plain = list_vault(&crate::vault::test_vault(), &request()).unwrap();
+    let items = serde_json::from_str::<serde_json::Value>(&plain.output).unwrap();
+    assert!(items[1].get("secrets").is_none());
+}
+
+use crate::vault::Cipher;
+use crate::vault::Vault;
+use crate::Session;
+use anyhow::anyhow;
+use rofi_bw_common::CipherString;
+use rofi_bw_common::CipherType;
+use rofi_bw_common::SymmetricKey;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use uuid::Uuid;
+use zeroize::Zeroize as _;
+use zeroize::Zeroizing;
@@ -0,0 +1,228 @@
+//! Locking the vault when the system suspends, the screen locks or the user goes idle.
+
+/// Start the threads that watch for the events enabled in `options`.
+pub(crate) fn spawn(options: &config::LockOn, display: &Display, locker: &Locker) {
+    if options.sleep || options.screen_lock {
+        let (sleep, screen_lock) = (options.sleep, options.screen_lock);
+        let locker = locker.clone();
+        spawn_thread("logind", move || watch_logind(sleep, screen_lock, &locker));
+    }
+
+    if options.screen_lock {
+        let locker = locker.clone();
+        spawn_thread("screensaver", move || watch_screensaver(&locker));
+    }
+
+    if let Some(idle) = options.idle {
+        let (display, locker) = (display.x11.clone(), locker.clone());
+        spawn_thread("idle", move || {
+            let display = display.context("idle time can only be read from X11")?;
+            watch_idle(&display, idle, &locker)
+        });
+    }
+}
+
+fn spawn_thread(name: &str, f: impl FnOnce() -> anyhow::Result<Infallible> + Send + 'static) {
+    let context = format!("failed to watch for {name} events");
+    let result = thread::Builder::new().spawn(move || {
+        let Err(e) = f();
+        let e = e.context(context);
+        eprintln!("Warning: {e:?}");
+    });
+    if let Err(e) = result {
+        let e = anyhow!(e).context("failed to spawn lock event thread");
+        eprintln!("Warning: {e:?}");
+    }
+}
+
+fn watch_logind(sleep: bool, screen_lock: bool, locker: &Locker) -> anyhow::Result<Infallible> {
+    let connection = Connection::new_system().context("failed to connect to system bus")?;
+
+    let session = match screen_lock {
+        true => Some(logind_session(&connection)?),
+        false => None,
+    };
+
+    for rule in logind_rules(sleep, session) {
+        let locker = locker.clone();
+        connection
+            .add_match(rule, move |(): (), _, message: &Message| {
+                if is_lock_signal(message) {
+                    locker.lock();
+                }
+                true
+            })
+            .context("failed to add match rule")?;
+    }
+
+    process_forever(&connection)
+}
+
+/// Find the object path of the logind session rofi-bw is running in.
+fn logind_session(connection: &Connection) -> anyhow::Result<Path<'static>> {
+    let manager = connection.with_proxy(LOGIND, LOGIND_PATH, TIMEOUT);
+    let (session,): (Path<'static>,) = match env::var("XDG_SESSION_ID") {
+        Ok(id) => manager.method_call(LOGIND_MANAGER, "GetSession", (id,)),
+        Err(_) => manager.method_call(LOGIND_MANAGER, "GetSessionByPID", (process::id(),)),
+    }
+    .context("failed to find logind session")?;
+    Ok(session)
+}
+
+fn logind_rules(sleep: bool, session: Option<Path<'static>>) -> Vec<MatchRule<'static>> {
+    let mut rules = Vec::new();
+    if sleep {
+        rules.push(
+            MatchRule::new_signal(LOGIND_MANAGER, "PrepareForSleep")
+                .with_sender(LOGIND)
+                .with_path(LOGIND_PATH),
+        );
+    }
+    if let Some(session) = session {
+        rules.push(
+            MatchRule::new_signal(LOGIND_SESSION, "Lock")
+                .with_sender(LOGIND)
+                .with_path(session),
+        );
+    }
+    rules
+}
+
+fn watch_screensaver(locker: &Locker) -> anyhow::Result<Infallible> {
+    let connection = Connection::new_session().context("failed to connect to session bus")?;
+
+    let locker = locker.clone();
+    connection
+        .add_match(screensaver_rule(), move |(): (), _, message: &Message| {
+            if is_lock_signal(message) {
+                locker.lock();
+            }
+            true
+        })
+        .context("failed to add match rule")?;
+
+    process_forever(&connection)
+}
+
+fn screensaver_rule() -> MatchRule<'static> {
+    MatchRule::new_signal(SCREENSAVER, "ActiveChanged")
+}
+
+/// Whether a signal matched by one of the rules above means the vault should be locked.
+///
+/// `PrepareForSleep` and `ActiveChanged` are also sent with `false` on resume and when the
+/// screensaver is dismissed.
+fn is_lock_signal(message: &Message) -> bool {
+    match message.member().as_deref() {
+        Some("Lock") => true,
+        Some("PrepareForSleep" | "ActiveChanged") => message.read1::<bool>().unwrap_or(false),
+        _ => false,
+    }
+}
+
+fn process_forever(connection: &Connection) -> anyhow::Result<Infallible> {
+    loop {
+        connection
+            .process(Duration::from_hours(1))
+            .context("failed to receive from bus")?;
+    }
+}
+
+/// Poll the X server’s idle time, locking once it exceeds `idle`.
+fn watch_idle(display: &str, idle: Duration, locker: &Locker) -> anyhow::Result<Infallible> {
+    let (connection, screen) =
+        x11rb::connect(Some(display)).context("failed to connect to X server")?;
+    let root = connection.setup().roots[screen].root;
+
+    connection
+        .screensaver_query_version(1, 1)
+        .context("failed to query screensaver extension")?
+        .reply()
+        .context("X server does not support the screensaver extension")?;
+
+    loop {
+        let info = connection
+            .screensaver_query_info(root)
+            .context("failed to query idle time")?
+            .reply()
+            .context("failed to query idle time")?;
+
+        let idle_for = Duration::from_millis(info.ms_since_user_input.into());
+        let wait = match idle.checked_sub(idle_for) {
+            Some(remaining) if !remaining.is_zero() => remaining,
+            _ => {
+                locker.lock();
+                idle
+            }
+        };
+        // Never poll more than once a second.
+        thread::sleep(wait.max(Duration::from_secs(1)));
+    }
+}
+
+const LOGIND: &str = "org.freedesktop.login1";
+const LOGIND_PATH: &str = "/org/freedesktop/login1";
+const LOGIND_MANAGER: &str = "org.freedesktop.login1.Manager";
+const LOGIND_SESSION: &str = "org.freedesktop.login1.Session";
+const SCREENSAVER: &str = "org.freedesktop.ScreenSaver";
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Stands in for the bus, delivering a signal to the callback if any rule matches it.
+#[cfg(test)]
+fn deliver(rules: &[MatchRule<'static>], message: &Message) -> bool {
+    rules.iter().any(|rule| rule.matches(message)) && is_lock_signal(message)
+}
+
+#[cfg(test)]
+fn signal(path: &str, interface: &str, member: &str) -> Message {
+    Message::new_signal(path, interface, member).unwrap()
+}
+
+#[test]
+fn sleep() {
+    let rules = logind_rules(true, None);
+    let sleep = |start| signal(LOGIND_PATH, LOGIND_MANAGER, "PrepareForSleep").append1(start);
+    assert!(deliver(&rules, &sleep(true)));
+    assert!(!deliver(&rules, &sleep(false)));
+}
+
+#[test]
+fn session_lock() {
+    let ours = Path::from("/org/freedesktop/login1/session/_32");
+    let rules = logind_rules(false, Some(ours.clone()));
+    let lock = |path: &str| signal(path, LOGIND_SESSION, "Lock");
+    assert!(deliver(&rules, &lock(&ours)));
+    assert!(!deliver(
+        &rules,
+        &lock("/org/freedesktop/login1/session/_33")
+    ));
+    let sleep = signal(LOGIND_PATH, LOGIND_MANAGER, "PrepareForSleep").append1(true);
+    assert!(!deliver(&rules, &sleep));
+}
+
+#[test]
+fn screensaver() {
+    let rules = [screensaver_rule()];
+    let active = |active| {
+        signal("/org/freedesktop/ScreenSaver", SCREENSAVER, "ActiveChanged").append1(active)
+    };
+    assert!(deliver(&rules, &active(true)));
+    assert!(!deliver(&rules, &active(false)));
+}
+
+use crate::config;
+use crate::daemon::Display;
+use crate::daemon::Locker;
+use anyhow::anyhow;
+use anyhow::Context as _;
+use dbus::blocking::Connection;
+use dbus::message::MatchRule;
+use dbus::strings::Path;
+use dbus::Message;
+use std::convert::Infallible;
+use std::env;
+use std::process;
+use std::thread;
+use std::time::Duration;
+use x11rb::connection::Connection as _;
+use x11rb::protocol::screensaver::ConnectionExt as _;
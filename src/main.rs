@@ -29,7 +29,8 @@ fn main() -> process::ExitCode {
 #[derive(clap::Parser)]
 #[clap(version)]
 #[clap(group = clap::ArgGroup::new("view").args(&[
-    "cipher-uuid", "cipher-name", "folder-uuid", "folder-name", "show", "reopen-last"
+    "cipher-uuid", "cipher-name", "folder-uuid", "folder-name", "show", "reopen-last", "uri",
+    "from-active-window"
 ]))]
 struct Args {
     /// The initial filter to use in Rofi.
@@ -60,16 +61,179 @@ struct Args {
     #[clap(long)]
     reopen_last: bool,
 
+    /// Have `rofi-bw` open showing the logins that match this URI, according to each URI’s match
+    /// detection setting.
+    #[clap(long)]
+    uri: Option<String>,
+
+    /// Like `--uri`, but take the URI from the title of the focused window, or from the output of
+    /// `active_window.command` if it is set.
+    #[clap(long)]
+    from_active_window: bool,
+
     /// Path to the config file; defaults to `$XDG_CONFIG_DIR/rofi-bw/config.toml`.
     ///
     /// Note that this will not be taken into account if an instance of rofi-bw is already running.
     #[clap(short, long)]
     config_file: Option<fs::PathBuf>,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(clap::Subcommand)]
+enum Command {
+    /// Create a Bitwarden Send and copy its link to the clipboard.
+    Send(SendArgs),
+
+    /// Print a value from an item in the unlocked vault.
+    ///
+    /// Each access must be confirmed in Rofi, unless `get.confirm` is disabled.
+    Get(GetArgs),
+
+    /// Act as a Git credential helper, giving out logins whose URI matches the repository’s host.
+    ///
+    /// Enable it with `git config --global credential.helper 'rofi-bw git-credential'`.
+    GitCredential(GitCredentialArgs),
+
+    /// List the items in the unlocked vault, optionally filtered.
+    ///
+    /// Secrets are only included with `--include-secrets`, which must be confirmed in Rofi unless
+    /// `get.confirm` is disabled; they are never included for items with master password reprompt.
+    List(ListArgs),
+
+    /// Download the latest version of the unlocked vault.
+    Sync,
+
+    /// Print whether rofi-bw is running and unlocked, and when it will lock.
+    Status(StatusArgs),
+
+    /// Lock the vault, stopping the running rofi-bw.
+    Lock,
+
+    /// Stop the running rofi-bw; since it only runs while the vault is unlocked, this is the same as
+    /// `lock`.
+    Quit,
+}
+
+#[derive(clap::Args)]
+struct StatusArgs {
+    /// Print the status as a JSON object, for status bars.
+    #[clap(long)]
+    json: bool,
+}
+
+#[derive(clap::Args)]
+#[clap(group = clap::ArgGroup::new("item").required(true).args(&["uuid", "name", "uri"]))]
+struct GetArgs {
+    /// The field to print: `password`, `username`, `totp` or the name of a custom field.
+    #[clap(default_value = "password")]
+    field: String,
+
+    /// The UUID of the item.
+    #[clap(long)]
+    uuid: Option<Uuid>,
+
+    /// The name of the item (must be an exact match).
+    #[clap(long)]
+    name: Option<String>,
+
+    /// A URI that one of the item’s URIs matches, according to its match detection setting.
+    #[clap(long)]
+    uri: Option<String>,
+
+    /// Only consider logins with this username.
+    #[clap(long)]
+    username: Option<String>,
+}
+
+#[derive(clap::Args)]
+struct ListArgs {
+    /// Print the items as a JSON array instead of one `UUID<tab>name` line per item.
+    #[clap(long)]
+    json: bool,
+
+    /// Only list items in the folder with this name; an empty name lists items in no folder.
+    #[clap(long)]
+    folder: Option<String>,
+
+    /// Only list items of this type.
+    #[clap(long = "type", value_enum)]
+    cipher_type: Option<ListType>,
+
+    /// Only list items whose name, username or URIs contain this text, ignoring case.
+    #[clap(long)]
+    search: Option<String>,
+
+    /// Include passwords, TOTP keys, notes and custom fields in the JSON output.
+    #[clap(long, requires = "json")]
+    include_secrets: bool,
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum ListType {
+    #[clap(alias = "logins")]
+    Login,
+    #[clap(alias = "secure-notes", alias = "notes", alias = "note")]
+    SecureNote,
+    #[clap(alias = "cards")]
+    Card,
+    #[clap(alias = "identities")]
+    Identity,
+    #[clap(alias = "ssh-keys")]
+    SshKey,
+}
+
+#[derive(clap::Args)]
+struct GitCredentialArgs {
+    /// The operation requested by Git; only `get` does anything.
+    operation: String,
+}
+
+#[derive(clap::Args)]
+struct SendArgs {
+    /// The text to send; read from standard input if neither this nor `--file` is given.
+    #[clap(conflicts_with = "file")]
+    text: Option<String>,
+
+    /// The path of a file to send instead of text.
+    #[clap(long)]
+    file: Option<fs::PathBuf>,
+
+    /// The name of the Send; defaults to the file name for file Sends.
+    #[clap(long)]
+    name: Option<String>,
+
+    /// How long until the Send is deleted, e.g. `12h` or `7d`; defaults to `send.deletion`.
+    #[clap(long, value_parser = parse_duration)]
+    deletion: Option<Duration>,
+
+    /// How long until the Send expires; defaults to `send.expiry`.
+    #[clap(long, value_parser = parse_duration)]
+    expiry: Option<Duration>,
+
+    /// How many times the Send can be accessed; defaults to `send.max_access_count`.
+    #[clap(long)]
+    max_access_count: Option<u32>,
+
+    /// Ask for a password that recipients must enter to access the Send.
+    #[clap(long)]
+    password: bool,
+
+    /// Hide the text from recipients by default.
+    #[clap(long, conflicts_with = "file")]
+    hidden: bool,
+}
+
+fn parse_duration(s: &str) -> Result<Duration, &'static str> {
+    config::parse_duration(s).ok_or("expected a duration like `30s`, `12m`, `1h` or `7d`")
 }
 
 #[derive(Clone, Copy, clap::ValueEnum)]
 enum Show {
     All,
+    #[clap(alias = "recently-used")]
+    Recent,
     #[clap(alias = "bin")]
     Trash,
     #[clap(alias = "favourite", alias = "favorites", alias = "favorite")]
@@ -82,11 +246,22 @@ enum Show {
     Cards,
     #[clap(alias = "identity")]
     Identities,
+    #[clap(alias = "ssh-key")]
+    SshKeys,
     #[clap(alias = "folder")]
     Folders,
+    #[clap(alias = "send")]
+    Sends,
 }
 
 fn try_main(args: Args) -> anyhow::Result<()> {
+    // Git also asks helpers to store and erase credentials, which we don’t support.
+    if let Some(Command::GitCredential(args)) = &args.command {
+        if args.operation != "get" {
+            return Ok(());
+        }
+    }
+
     let ProcessedArgs {
         request,
         config_file,
@@ -98,75 +273,157 @@ fn try_main(args: Args) -> anyhow::Result<()> {
         .runtime_dir()
         .context("failed to locate runtime directory")?;
 
-    let request = daemon::Request::ShowMenu(request);
-    if daemon::invoke(runtime_dir, &request)? {
+    let output = daemon::invoke(runtime_dir, &request)?;
+    let output = match request {
+        daemon::Request::ShowMenu(_) | daemon::Request::Send(_) => {
+            if output.is_some() {
+                return Ok(());
+            }
+            None
+        }
+        // These need an unlocked vault, so there’s no point in becoming the daemon and asking for
+        // the master password.
+        daemon::Request::Get(_) | daemon::Request::List(_) | daemon::Request::Sync => {
+            Some(output.context("rofi-bw is not running; open it to unlock the vault")?)
+        }
+        daemon::Request::Status { json } => Some(match output {
+            Some(output) => output,
+            None => {
+                let email = Data::load(project_dirs.data_dir())?.email;
+                Status::not_running(email).format(json)
+            }
+        }),
+        // If there’s no daemon, the vault is already locked.
+        daemon::Request::Lock | daemon::Request::Quit => None,
+    };
+    if let Some(output) = output {
+        io::stdout()
+            .write_all(output.as_bytes())
+            .context("failed to write to stdout")?;
+        return Ok(());
+    }
+    if let daemon::Request::Lock | daemon::Request::Quit = request {
         return Ok(());
     }
-    let daemon::Request::ShowMenu(request) = request else { unreachable!() };
 
     // Having failed to invoke an existing daemon, we must now become the daemon.
+    run_daemon(&project_dirs, runtime_dir, config_file, request)
+}
+
+fn run_daemon(
+    project_dirs: &ProjectDirs,
+    runtime_dir: &fs::Path,
+    config_file: Option<fs::PathBuf>,
+    request: daemon::Request,
+) -> anyhow::Result<()> {
+    let mut display = daemon::Display::from_env()?;
 
     let config_path = config_file.unwrap_or_else(|| project_dirs.config_dir().join("config.toml"));
-    let Config {
-        auto_lock,
-        copy_notification,
-        rofi_options,
-        client_id,
-        device_type,
-        device_name,
-    } = config::load(&config_path)?;
+    let config = config::load(&config_path)?;
+
+    let mut daemon = Daemon::bind(runtime_dir, config.auto_lock)?;
+
+    lock_events::spawn(&config.lock_on, &display, &daemon.locker());
 
-    let mut daemon = Daemon::bind(runtime_dir, auto_lock)?;
+    let ssh_agent = (config.ssh_agent.enabled)
+        .then(|| ssh_agent::Agent::bind(runtime_dir))
+        .transpose()?;
+    let ssh_agent = ssh_agent.as_ref();
 
     let http = ureq::agent();
 
-    let mut session_manager =
-        SessionManager::new(&project_dirs, &http, &client_id, device_type, device_name)?;
+    let mut session_manager = SessionManager::new(
+        project_dirs,
+        &http,
+        &config.client_id,
+        config.device_type,
+        config.device_name,
+    )?;
 
     let mut menu_opts = MenuOpts {
-        lib_dir: match fs::path::List::from_env_var("ROFI_BW_LIB_DIR") {
-            Some(dir) => dir,
-            None => fs::path::List::from_ref("/usr/lib/rofi-bw:/usr/local/lib/rofi-bw").to_boxed(),
-        },
-        rofi_options,
-        copy_notification,
-        clipboard: Clipboard::new().context("failed to open clipboard")?,
+        lib_dir: lib_dir(),
+        rofi_options: config.rofi_options,
+        copy_notification: config.copy_notification,
+        clipboard_target: config.clipboard_target,
+        sort: config.sort,
+        usage: UsageFile::load(project_dirs.data_dir()),
+        attachments: config.attachments,
+        send: config.send,
+        max_session_age: config.auto_lock.max,
+        search_fields: config.search.fields(),
+        keybinds: config.keybinds,
+        default_copy: config.default_copy,
+        clipboard: Clipboard::default(),
+        locker: daemon.locker(),
     };
 
-    let mut display = request.display;
-    let mut menu_state = MenuState {
-        filter: request.filter,
-        history: request.view.map_or_else(History::default, History::new),
-    };
+    let mut menu_state = MenuState::default();
+    let mut pending_send = None;
+    match request {
+        daemon::Request::ShowMenu(request) => {
+            let view = requested_view(&request, &config.active_window);
+            display = request.display;
+            menu_state.filter = request.filter;
+            menu_state.history = view.map_or_else(History::default, History::new);
+        }
+        daemon::Request::Send(request) => pending_send = Some(request),
+        // `try_main` answers every other request without becoming the daemon.
+        _ => unreachable!(),
+    }
 
-    while let Some(mut session) = session_manager.start_session()? {
+    while let Some(mut session) = session_manager.start_session(&display)? {
         loop {
-            let after_menu = show_menu(
-                &mut session_manager,
-                session,
-                &mut menu_opts,
-                &display,
-                &mut menu_state,
-            );
+            if menu_opts.max_session_age.has_passed(session.unlocked_at()) {
+                // Ask for the master password again, however active the user has been.
+                share_session(&daemon, ssh_agent, &display, None);
+                break;
+            }
+
+            share_session(&daemon, ssh_agent, &display, Some(&mut session));
+
+            let mut after_menu = match pending_send.take() {
+                Some(request) => run_pending_send(
+                    session,
+                    &mut menu_opts,
+                    &display,
+                    request,
+                    &mut pending_send,
+                ),
+                None => show_menu(
+                    &mut session_manager,
+                    session,
+                    &mut menu_opts,
+                    &display,
+                    &mut menu_state,
+                ),
+            };
+
+            // Keep the agent in step with the menu before waiting, since it may have synced or
+            // locked the vault.
+            share_session(&daemon, ssh_agent, &display, after_menu.session.as_mut());
 
             if !after_menu.reshow && after_menu.session.is_none() {
                 // If we don’t have to show another menu and don’t have an active session, there’s
                 // no need to keep running.
                 return Ok(());
             } else if !after_menu.reshow {
-                match daemon.wait() {
-                    daemon::Request::ShowMenu(daemon::ShowMenu {
-                        display: new_display,
-                        filter,
-                        view,
-                    }) => {
-                        display = new_display;
-                        menu_state.filter = filter;
-                        if let Some(view) = view {
+                match wait_for_menu_request(
+                    &mut daemon,
+                    &mut after_menu.session,
+                    ssh_agent,
+                    &config.get,
+                    &display,
+                ) {
+                    Woken::ShowMenu(request) => {
+                        if let Some(view) = requested_view(&request, &config.active_window) {
                             menu_state.history.push(view);
                         }
+                        display = request.display;
+                        menu_state.filter = request.filter;
                     }
-                    daemon::Request::Quit => return Ok(()),
+                    Woken::Send(request) => pending_send = Some(request),
+                    Woken::Expired => {}
+                    Woken::Quit => return Ok(()),
                 }
             }
 
@@ -180,8 +437,71 @@ fn try_main(args: Args) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// What woke the daemon while no menu was shown.
+enum Woken {
+    ShowMenu(daemon::ShowMenu),
+    Send(send::Request),
+    /// The session expired while syncing, so the vault must be unlocked again.
+    Expired,
+    Quit,
+}
+
+/// Wait for a request that needs the menu, answering the ones that don’t in the meantime.
+fn wait_for_menu_request(
+    daemon: &mut Daemon,
+    session: &mut Option<Session<'_, '_>>,
+    ssh_agent: Option<&ssh_agent::Agent>,
+    get: &config::Get,
+    display: &daemon::Display,
+) -> Woken {
+    loop {
+        match daemon.wait() {
+            (daemon::Request::ShowMenu(request), _) => return Woken::ShowMenu(request),
+            (daemon::Request::Send(request), _) => return Woken::Send(request),
+            (daemon::Request::Get(request), replier) => {
+                replier.reply(run_get(session.as_ref().unwrap(), get, display, &request));
+            }
+            (daemon::Request::List(request), replier) => {
+                replier.reply(run_list(session.as_ref().unwrap(), get, display, &request));
+            }
+            (daemon::Request::Sync, replier) => {
+                let result = resync(session);
+                share_session(daemon, ssh_agent, display, session.as_mut());
+                if result.is_ok() && session.is_none() {
+                    replier.reply(Err(anyhow::anyhow!(
+                        "session expired; enter the master password to unlock again"
+                    )));
+                    return Woken::Expired;
+                }
+                replier.reply(result.map(|()| String::new()));
+            }
+            (daemon::Request::Status { .. }, _) => unreachable!(),
+            // The daemon only runs while the vault is unlocked, so locking it means quitting.
+            (daemon::Request::Lock | daemon::Request::Quit, _) => return Woken::Quit,
+        }
+    }
+}
+
+/// The view a request to show the menu asks for, reading the URI of the active window if it asks
+/// for that. If reading it fails, the error is reported and the last view in history is shown.
+fn requested_view(
+    request: &daemon::ShowMenu,
+    active_window: &config::ActiveWindow,
+) -> Option<ipc::View> {
+    if request.from_active_window {
+        return match active_window::uri(&request.display, active_window) {
+            Ok(uri) => Some(ipc::View::Uri(uri)),
+            Err(e) => {
+                report_error(e.context("failed to read URI of active window").as_ref());
+                None
+            }
+        };
+    }
+    request.view.clone()
+}
+
 struct ProcessedArgs {
-    request: daemon::ShowMenu,
+    request: daemon::Request,
     config_file: Option<fs::PathBuf>,
 }
 
@@ -195,9 +515,32 @@ fn process_args(
         show,
         config_file,
         reopen_last,
+        uri,
+        from_active_window,
+        command,
     }: Args,
 ) -> anyhow::Result<ProcessedArgs> {
-    let display = env::var("DISPLAY").context("failed to read `$DISPLAY` env var")?;
+    let request = match command {
+        Some(Command::Send(args)) => Some(daemon::Request::Send(process_send_args(args)?)),
+        Some(Command::Get(args)) => Some(daemon::Request::Get(process_get_args(args))),
+        Some(Command::GitCredential(_)) => {
+            Some(daemon::Request::Get(read_git_credential_request()?))
+        }
+        Some(Command::List(args)) => Some(daemon::Request::List(process_list_args(args))),
+        Some(Command::Sync) => Some(daemon::Request::Sync),
+        Some(Command::Status(StatusArgs { json })) => Some(daemon::Request::Status { json }),
+        Some(Command::Lock) => Some(daemon::Request::Lock),
+        Some(Command::Quit) => Some(daemon::Request::Quit),
+        None => None,
+    };
+    if let Some(request) = request {
+        return Ok(ProcessedArgs {
+            request,
+            config_file,
+        });
+    }
+
+    let display = daemon::Display::from_env()?;
 
     let request = daemon::ShowMenu {
         display,
@@ -208,42 +551,223 @@ fn process_args(
             folder_uuid,
             folder_name,
             show,
-            reopen_last,
+            reopen_last || from_active_window,
+            uri,
         ) {
-            (Some(uuid), None, None, None, None, false) => {
+            (Some(uuid), None, None, None, None, false, None) => {
                 Some(ipc::View::Cipher(ipc::Filter::Uuid(uuid.into_bytes())))
             }
-            (None, Some(name), None, None, None, false) => {
+            (None, Some(name), None, None, None, false, None) => {
                 Some(ipc::View::Cipher(ipc::Filter::Name(name)))
             }
-            (None, None, Some(uuid), None, None, false) => {
+            (None, None, Some(uuid), None, None, false, None) => {
                 Some(ipc::View::Folder(ipc::Filter::Uuid(uuid.into_bytes())))
             }
-            (None, None, None, Some(name), None, false) => {
+            (None, None, None, Some(name), None, false, None) => {
                 Some(ipc::View::Folder(ipc::Filter::Name(name)))
             }
-            (None, None, None, None, Some(show), false) => Some(ipc::View::List(match show {
-                Show::All => List::All,
-                Show::Trash => List::Trash,
-                Show::Favourites => List::Favourites,
-                Show::Logins => List::TypeBucket(CipherType::Login),
-                Show::SecureNotes => List::TypeBucket(CipherType::SecureNote),
-                Show::Cards => List::TypeBucket(CipherType::Card),
-                Show::Identities => List::TypeBucket(CipherType::Identity),
-                Show::Folders => List::Folders,
-            })),
-            (None, None, None, None, None, true) => None,
-            (None, None, None, None, None, false) => Some(ipc::View::default()),
+            (None, None, None, None, None, false, Some(uri)) => Some(ipc::View::Uri(uri)),
+            (None, None, None, None, Some(show), false, None) => {
+                Some(ipc::View::List(match show {
+                    Show::All => List::All,
+                    Show::Recent => List::Recent,
+                    Show::Trash => List::Trash,
+                    Show::Favourites => List::Favourites,
+                    Show::Logins => List::TypeBucket(CipherType::Login),
+                    Show::SecureNotes => List::TypeBucket(CipherType::SecureNote),
+                    Show::Cards => List::TypeBucket(CipherType::Card),
+                    Show::Identities => List::TypeBucket(CipherType::Identity),
+                    Show::SshKeys => List::TypeBucket(CipherType::SshKey),
+                    Show::Folders => List::Folders,
+                    Show::Sends => List::Sends,
+                }))
+            }
+            // The URI of the active window is read by the daemon once it receives the request.
+            (None, None, None, None, None, true, None) => None,
+            (None, None, None, None, None, false, None) => Some(ipc::View::default()),
             _ => unreachable!("args are mutually exclusive"),
         },
+        from_active_window,
     };
 
     Ok(ProcessedArgs {
-        request,
+        request: daemon::Request::ShowMenu(request),
         config_file,
     })
 }
 
+fn process_get_args(
+    GetArgs {
+        field,
+        uuid,
+        name,
+        uri,
+        username,
+    }: GetArgs,
+) -> get::Request {
+    let item = match (uuid, name, uri) {
+        (Some(uuid), None, None) => get::Item::Uuid(uuid.into_bytes()),
+        (None, Some(name), None) => get::Item::Name(name),
+        (None, None, Some(uri)) => get::Item::Uri(uri),
+        _ => unreachable!("args are mutually exclusive and required"),
+    };
+
+    let field = match &*field {
+        "password" => get::Field::Password,
+        "username" => get::Field::Username,
+        "totp" => get::Field::Totp,
+        _ => get::Field::Custom(field),
+    };
+
+    get::Request {
+        item,
+        username,
+        output: get::Output::Field(field),
+    }
+}
+
+fn process_list_args(
+    ListArgs {
+        json,
+        folder,
+        cipher_type,
+        search,
+        include_secrets,
+    }: ListArgs,
+) -> list::Request {
+    list::Request {
+        folder,
+        cipher_type: cipher_type.map(|cipher_type| match cipher_type {
+            ListType::Login => CipherType::Login,
+            ListType::SecureNote => CipherType::SecureNote,
+            ListType::Card => CipherType::Card,
+            ListType::Identity => CipherType::Identity,
+            ListType::SshKey => CipherType::SshKey,
+        }),
+        search,
+        include_secrets,
+        json,
+    }
+}
+
+/// Read the description of the credential Git wants from standard input.
+fn read_git_credential_request() -> anyhow::Result<get::Request> {
+    let mut input = String::new();
+    io::stdin()
+        .read_to_string(&mut input)
+        .context("failed to read credential description from stdin")?;
+
+    let mut url = None;
+    let mut protocol = None;
+    let mut host = None;
+    let mut path = None;
+    let mut username = None;
+    for line in input.lines() {
+        if line.is_empty() {
+            break;
+        }
+        let (key, value) = line
+            .split_once('=')
+            .with_context(|| format!("invalid credential attribute line `{line}`"))?;
+        let value = value.to_owned();
+        match key {
+            "url" => url = Some(value),
+            "protocol" => protocol = Some(value),
+            "host" => host = Some(value),
+            "path" => path = Some(value),
+            "username" => username = Some(value),
+            _ => {}
+        }
+    }
+
+    let url = match (url, protocol, host) {
+        (Some(url), _, _) => url,
+        (None, Some(protocol), Some(host)) => {
+            format!("{protocol}://{host}/{}", path.unwrap_or_default())
+        }
+        _ => anyhow::bail!("Git did not say which host it needs credentials for"),
+    };
+
+    Ok(get::Request {
+        item: get::Item::Uri(url),
+        username,
+        output: get::Output::GitCredential,
+    })
+}
+
+fn process_send_args(
+    SendArgs {
+        text,
+        file,
+        name,
+        deletion,
+        expiry,
+        max_access_count,
+        password,
+        hidden,
+    }: SendArgs,
+) -> anyhow::Result<send::Request> {
+    let (content, default_name) = match (text, file) {
+        (None, Some(path)) => {
+            let data = fs::read(&*path)?;
+            let file_name = path
+                .file_name()
+                .context("file path has no file name")?
+                .to_string_lossy()
+                .into_owned();
+            let name = file_name.clone();
+            (send::Content::File { file_name, data }, name)
+        }
+        (text, None) => {
+            let text = match text {
+                Some(text) => text,
+                None => {
+                    let mut text = String::new();
+                    io::stdin()
+                        .read_to_string(&mut text)
+                        .context("failed to read text to send from stdin")?;
+                    text
+                }
+            };
+            let content = send::Content::Text { text, hidden };
+            (content, rofi_bw_common::send::DEFAULT_NAME.to_owned())
+        }
+        (Some(_), Some(_)) => unreachable!("args are mutually exclusive"),
+    };
+
+    let password = if password {
+        let password = ask_send_password()?.context("no Send password was entered")?;
+        Some(String::clone(&password))
+    } else {
+        None
+    };
+
+    Ok(send::Request {
+        name: name.unwrap_or(default_name),
+        content,
+        deletion,
+        expiry,
+        max_access_count,
+        password,
+    })
+}
+
+fn ask_send_password() -> anyhow::Result<Option<Zeroizing<String>>> {
+    let mut password = Zeroizing::new(String::with_capacity(1024));
+
+    let mut dmenu = process::Command::new("rofi");
+    dmenu.arg("-dmenu").stdin(process::Stdio::null());
+    dmenu.arg("-p").arg("Send password").arg("-password");
+
+    let outcome = run_dmenu(dmenu, &mut password).context("failed to prompt for Send password")?;
+
+    if outcome == run_dmenu::Outcome::Cancelled || password.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(password))
+}
+
 struct SessionManager<'dirs, 'http, 'client_id> {
     project_dirs: &'dirs ProjectDirs,
     http: &'http ureq::Agent,
@@ -271,10 +795,13 @@ impl<'dirs, 'http, 'client_id> SessionManager<'dirs, 'http, 'client_id> {
         })
     }
 
-    fn start_session(&mut self) -> anyhow::Result<Option<Session<'http, 'client_id>>> {
+    fn start_session(
+        &mut self,
+        display: &daemon::Display,
+    ) -> anyhow::Result<Option<Session<'http, 'client_id>>> {
         loop {
             if self.data.email.is_none() {
-                self.data.email = Some(match ask_email()? {
+                self.data.email = Some(match ask_email(display)? {
                     Some(email) => email,
                     None => return Ok(None),
                 });
@@ -285,11 +812,11 @@ impl<'dirs, 'http, 'client_id> SessionManager<'dirs, 'http, 'client_id> {
             let mut again = false;
             loop {
                 let keybinds = &[Keybind {
-                    combination: "Control+o",
+                    combination: Cow::Borrowed("Control+o"),
                     action: (),
                     description: "Log out",
                 }];
-                let master_password = match ask_master_password(again, "", keybinds)? {
+                let master_password = match ask_master_password(display, again, "", keybinds)? {
                     ask_master_password::Outcome::Ok(master_password) => master_password,
                     ask_master_password::Outcome::Cancelled => return Ok(None),
                     ask_master_password::Outcome::Custom(&()) => {
@@ -341,14 +868,33 @@ struct MenuOpts {
     lib_dir: Box<fs::path::List>,
     rofi_options: config::RofiOptions,
     copy_notification: bool,
+    clipboard_target: config::ClipboardTarget,
+    sort: Sort,
+    usage: UsageFile,
+    attachments: config::Attachments,
+    send: config::Send,
+    max_session_age: config::AutoLock,
+    search_fields: ipc::SearchFields,
+    keybinds: menu_keybinds::Combinations,
+    default_copy: DefaultCopy,
     clipboard: Clipboard,
+    /// Closes the menu when the vault is locked from elsewhere.
+    locker: daemon::Locker,
+}
+
+/// The directories to search for the plugin, which can be overridden with `ROFI_BW_LIB_DIR`.
+fn lib_dir() -> Box<fs::path::List> {
+    match fs::path::List::from_env_var("ROFI_BW_LIB_DIR") {
+        Some(dir) => dir,
+        None => fs::path::List::from_ref("/usr/lib/rofi-bw:/usr/local/lib/rofi-bw").to_boxed(),
+    }
 }
 
 fn show_menu<'http, 'client_id>(
     session_manager: &mut SessionManager<'_, '_, '_>,
     session: Session<'http, 'client_id>,
     opts: &mut MenuOpts,
-    display: &str,
+    display: &daemon::Display,
     menu_state: &mut MenuState,
 ) -> AfterMenu<'http, 'client_id> {
     let mut session = Some(session);
@@ -360,11 +906,32 @@ fn show_menu<'http, 'client_id>(
     AfterMenu { session, reshow }
 }
 
+/// Create a Send requested from the command line. If the session expired, the request is put back
+/// so it can be tried again once the user has logged back in.
+fn run_pending_send<'http, 'client_id>(
+    session: Session<'http, 'client_id>,
+    opts: &mut MenuOpts,
+    display: &daemon::Display,
+    request: send::Request,
+    pending_send: &mut Option<send::Request>,
+) -> AfterMenu<'http, 'client_id> {
+    let mut session = Some(session);
+    if let Err(e) = create_send(&mut session, opts, display, &request) {
+        report_error(e.as_ref());
+    }
+    let reshow = session.is_none();
+    if reshow {
+        *pending_send = Some(request);
+    }
+    AfterMenu { session, reshow }
+}
+
+#[allow(clippy::too_many_lines)]
 fn try_show_menu(
     session_manager: &mut SessionManager<'_, '_, '_>,
     session_option: &mut Option<Session<'_, '_>>,
     opts: &mut MenuOpts,
-    display: &str,
+    display: &daemon::Display,
     menu_state: &mut MenuState,
 ) -> anyhow::Result<bool> {
     let session = session_option.as_mut().unwrap();
@@ -373,6 +940,18 @@ fn try_show_menu(
         master_key: session.master_key(),
         data: session.account_data().as_bytes(),
         history: &menu_state.history,
+        usage: opts.usage.record(),
+        sort: opts.sort,
+        lock_in: match opts.max_session_age {
+            config::AutoLock::Never => None,
+            config::AutoLock::After(max) => Some(
+                max.saturating_sub(session.unlocked_at().elapsed())
+                    .as_secs(),
+            ),
+        },
+        search_fields: opts.search_fields,
+        keybinds: &opts.keybinds,
+        default_copy: &opts.default_copy,
     };
 
     let res = menu::run(
@@ -381,44 +960,137 @@ fn try_show_menu(
         &opts.rofi_options,
         display,
         &menu_state.filter,
+        &opts.locker,
     )?;
 
+    if let Some(new_menu_state) = res.menu_state().filter(|state| !state.opened.is_empty()) {
+        match session.user_key() {
+            Ok(key) => opts.usage.record_opens(&key, &new_menu_state.opened),
+            Err(e) => eprintln!("Warning: {:?}", anyhow::Error::new(e)),
+        }
+    }
+
     Ok(match res {
         ipc::MenuRequest::Copy {
             cipher_name,
+            cipher_id,
             field,
             data,
+            then,
+            secret,
+            other_selection,
             image_path,
             reprompt,
             menu_state: new_menu_state,
         } => {
             *menu_state = new_menu_state;
 
-            if reprompt && !run_reprompt(session, &cipher_name)? {
+            if reprompt && !run_reprompt(session, display, &cipher_name)? {
                 return Ok(true);
             }
 
-            opts.clipboard
-                .set_text(data)
-                .context("failed to set clipboard content")?;
-
-            if opts.copy_notification {
-                show_notification(format!("copied {cipher_name} {field}"), image_path);
+            let steps = iter::once(CopyStep { field, data }).chain(then).collect();
+            copy_sequence(
+                opts,
+                display,
+                cipher_name,
+                image_path,
+                steps,
+                secret,
+                other_selection,
+            )?;
+
+            if let Some(cipher_id) = cipher_id {
+                match session.user_key() {
+                    Ok(key) => opts.usage.record_use(&key, cipher_id),
+                    Err(e) => eprintln!("Warning: {:?}", anyhow::Error::new(e)),
+                }
             }
 
             false
         }
+        ipc::MenuRequest::SaveAttachment {
+            cipher_name,
+            cipher_id,
+            attachment_id,
+            file_name,
+            key,
+            reprompt,
+            menu_state: new_menu_state,
+        } => {
+            *menu_state = new_menu_state;
+
+            if reprompt && !run_reprompt(session, display, &cipher_name)? {
+                return Ok(true);
+            }
+
+            save_attachment(
+                session_option,
+                &opts.attachments,
+                &cipher_name,
+                Uuid::from_bytes(cipher_id),
+                &attachment_id,
+                &file_name,
+                key.as_deref(),
+            )?;
+
+            // Show the menu again after logging back in if the session expired.
+            session_option.is_none()
+        }
+        ipc::MenuRequest::CreateSend {
+            name,
+            text,
+            hidden,
+            reprompt_cipher,
+            menu_state: new_menu_state,
+        } => {
+            *menu_state = new_menu_state;
+
+            if let Some(cipher_name) = reprompt_cipher {
+                if !run_reprompt(session, display, &cipher_name)? {
+                    return Ok(true);
+                }
+            }
+
+            let request = send::Request::text(name, text, hidden);
+            create_send(session_option, opts, display, &request)?;
+
+            // Show the menu again after logging back in if the session expired.
+            session_option.is_none()
+        }
         ipc::MenuRequest::Sync {
             menu_state: new_menu_state,
         } => {
             *menu_state = new_menu_state;
-            match session.resync() {
-                Ok(()) => {}
-                Err(session::ResyncError::Refresh(auth::refresh::Error::SessionExpired(_))) => {
+            resync(session_option)?;
+            true
+        }
+        ipc::MenuRequest::EditFolder {
+            edit,
+            menu_state: new_menu_state,
+        } => {
+            *menu_state = new_menu_state;
+            match session.edit_folder(&edit) {
+                Ok(()) => resync(session_option)?,
+                Err(session::EditFolderError::Refresh(auth::refresh::Error::SessionExpired(_))) => {
                     *session_option = None;
                 }
                 Err(e) => return Err(e.into()),
-            };
+            }
+            true
+        }
+        ipc::MenuRequest::EditSend {
+            edit,
+            menu_state: new_menu_state,
+        } => {
+            *menu_state = new_menu_state;
+            match session.edit_send(&edit) {
+                Ok(()) => resync(session_option)?,
+                Err(session::EditSendError::Refresh(auth::refresh::Error::SessionExpired(_))) => {
+                    *session_option = None;
+                }
+                Err(e) => return Err(e.into()),
+            }
             true
         }
         ipc::MenuRequest::Lock => {
@@ -440,14 +1112,223 @@ fn try_show_menu(
     })
 }
 
-fn run_reprompt(session: &Session<'_, '_>, cipher_name: &str) -> anyhow::Result<bool> {
+/// Copy each step in turn to the configured selection, notifying the user of each copy.
+fn copy_sequence(
+    opts: &mut MenuOpts,
+    display: &daemon::Display,
+    cipher_name: String,
+    image_path: Option<String>,
+    steps: Vec<CopyStep>,
+    secret: bool,
+    other_selection: bool,
+) -> anyhow::Result<()> {
+    let (names, texts) = steps
+        .into_iter()
+        .map(|step| (step.field, step.data))
+        .unzip::<_, _, Vec<_>, Vec<_>>();
+
+    let copy_notification = opts.copy_notification;
+    let notify = move |step: usize| {
+        if !copy_notification {
+            return;
+        }
+        let field = &names[step];
+        let summary = match names.len() {
+            1 => format!("copied {cipher_name} {field}"),
+            len => format!("copied {cipher_name} {field} ({}/{len})", step + 1),
+        };
+        show_notification(summary, image_path.clone());
+    };
+
+    let target = match other_selection {
+        true => opts.clipboard_target.other(),
+        false => opts.clipboard_target,
+    };
+    opts.clipboard
+        .set_sequence(display, texts, target, secret, notify.clone())?;
+
+    notify(0);
+
+    Ok(())
+}
+
+/// Let the SSH agent and other clients use the session, or stop them from using it if it has ended.
+fn share_session(
+    daemon: &Daemon,
+    ssh_agent: Option<&ssh_agent::Agent>,
+    display: &daemon::Display,
+    mut session: Option<&mut Session<'_, '_>>,
+) {
+    if let Some(agent) = ssh_agent {
+        update_ssh_agent(agent, display, session.as_deref_mut());
+    }
+    daemon.set_session(session.as_deref());
+}
+
+/// Serve the SSH keys of the session’s vault, or none if the session has ended.
+fn update_ssh_agent(
+    agent: &ssh_agent::Agent,
+    display: &daemon::Display,
+    session: Option<&mut Session<'_, '_>>,
+) {
+    match session {
+        Some(session) => {
+            if let Err(e) = agent.unlock(session, display) {
+                report_error(e.context("failed to load SSH keys").as_ref());
+            }
+        }
+        None => agent.lock(),
+    }
+}
+
+/// Find a value for `rofi-bw get` or `rofi-bw git-credential`, confirming access with the user.
+fn run_get(
+    session: &Session<'_, '_>,
+    options: &config::Get,
+    display: &daemon::Display,
+    request: &get::Request,
+) -> anyhow::Result<String> {
+    let found = get::find(session, request)?;
+
+    let allowed = if found.reprompt {
+        run_reprompt(session, display, &found.name)?
+    } else if options.confirm {
+        confirm(
+            display,
+            &format!("Allow access to the {} of {}?", found.what, found.name),
+        )?
+    } else {
+        true
+    };
+    anyhow::ensure!(allowed, "access to {} was denied", found.name);
+
+    Ok(found.output.to_string())
+}
+
+/// List items for `rofi-bw list`, confirming access with the user if secrets are included.
+fn run_list(
+    session: &Session<'_, '_>,
+    options: &config::Get,
+    display: &daemon::Display,
+    request: &list::Request,
+) -> anyhow::Result<String> {
+    let listed = list::list(session, request)?;
+
+    if request.include_secrets && options.confirm {
+        let allowed = confirm(
+            display,
+            &format!("Allow access to the secrets of {} item(s)?", listed.count),
+        )?;
+        anyhow::ensure!(allowed, "access to secrets was denied");
+    }
+
+    Ok(listed.output.to_string())
+}
+
+/// Resynchronize the session’s vault data, ending the session if it has expired.
+fn resync(session_option: &mut Option<Session<'_, '_>>) -> anyhow::Result<()> {
+    let session = session_option.as_mut().unwrap();
+    match session.resync() {
+        Ok(()) => {}
+        Err(session::ResyncError::Refresh(auth::refresh::Error::SessionExpired(_))) => {
+            *session_option = None;
+        }
+        Err(e) => return Err(e.into()),
+    }
+    Ok(())
+}
+
+/// Download and decrypt an attachment, then either save it or pass it to the configured opener.
+/// Ends the session if it has expired.
+fn save_attachment(
+    session_option: &mut Option<Session<'_, '_>>,
+    options: &config::Attachments,
+    cipher_name: &str,
+    cipher_id: Uuid,
+    attachment_id: &str,
+    file_name: &str,
+    key: Option<&str>,
+) -> anyhow::Result<()> {
+    let session = session_option.as_mut().unwrap();
+    let key = key
+        .map(str::parse::<CipherString<Vec<u8>>>)
+        .transpose()
+        .context("invalid attachment key")?;
+    let key = attachment::key(&session.user_key()?, key.as_ref())?;
+
+    let data = match session.download_attachment(cipher_id, attachment_id) {
+        Ok(data) => data,
+        Err(session::DownloadAttachmentError::Refresh(auth::refresh::Error::SessionExpired(_))) => {
+            *session_option = None;
+            return Ok(());
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let contents = Zeroizing::new(attachment::decrypt(&key, &data)?);
+
+    if let Some(opener) = &options.opener {
+        return attachment::open_with(opener, &contents).context("failed to open attachment");
+    }
+
+    let dir = match &options.dir {
+        Some(dir) => dir.clone(),
+        None => UserDirs::new()
+            .and_then(|dirs| dirs.download_dir().map(fs::Path::to_owned))
+            .context("no downloads directory; set `attachments.dir` in the config")?,
+    };
+    let path = attachment::save(&dir, file_name, &contents).context("failed to save attachment")?;
+
+    show_notification(
+        format!("saved {cipher_name} attachment to {}", path.display()),
+        None,
+    );
+
+    Ok(())
+}
+
+/// Create a Send and copy its link to the clipboard, ending the session if it has expired.
+fn create_send(
+    session_option: &mut Option<Session<'_, '_>>,
+    opts: &mut MenuOpts,
+    display: &daemon::Display,
+    request: &send::Request,
+) -> anyhow::Result<()> {
+    let session = session_option.as_mut().unwrap();
+    let link = match send::create(session, &opts.send, request) {
+        Ok(link) => link,
+        Err(send::CreateError::Create(session::CreateSendError::Refresh(
+            auth::refresh::Error::SessionExpired(_),
+        ))) => {
+            *session_option = None;
+            return Ok(());
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    opts.clipboard
+        .set_text(display, link, opts.clipboard_target)?;
+
+    if opts.copy_notification {
+        show_notification(format!("copied link to Send {}", request.name), None);
+    }
+
+    Ok(())
+}
+
+fn run_reprompt(
+    session: &Session<'_, '_>,
+    display: &daemon::Display,
+    cipher_name: &str,
+) -> anyhow::Result<bool> {
     let status = format!(
         "The item \"{cipher_name}\" is protected and requires verifying your master password"
     );
 
     let mut again = false;
     Ok(loop {
-        let master_password = match ask_master_password::<Infallible>(again, &status, &[])? {
+        let master_password = match ask_master_password::<Infallible>(display, again, &status, &[])?
+        {
             ask_master_password::Outcome::Ok(password) => password,
             ask_master_password::Outcome::Cancelled => break false,
             ask_master_password::Outcome::Custom(&unreachable) => match unreachable {},
@@ -459,15 +1340,57 @@ fn run_reprompt(session: &Session<'_, '_>, cipher_name: &str) -> anyhow::Result<
     })
 }
 
+use confirm::confirm;
+mod confirm {
+    /// Ask the user to allow or deny something, waiting for the menu to close first if it is open.
+    pub(crate) fn confirm(display: &daemon::Display, message: &str) -> anyhow::Result<bool> {
+        let mut rofi = process::Command::new("rofi");
+        rofi.arg("-dmenu").arg("-no-custom");
+        rofi.arg("-p").arg("rofi-bw").arg("-mesg").arg(message);
+        display.apply_to_rofi(&mut rofi);
+        rofi.stdin(process::Stdio::piped())
+            .stdout(process::Stdio::piped());
+
+        let _showing = menu::SHOWING.lock().unwrap();
+        let mut rofi = rofi.spawn().context("failed to spawn rofi")?;
+
+        let mut stdin = rofi.stdin.take().unwrap();
+        stdin
+            .write_all(b"Allow\nDeny\n")
+            .context("failed to write to Rofi")?;
+        drop(stdin);
+
+        let mut choice = String::new();
+        rofi.stdout
+            .take()
+            .unwrap()
+            .read_to_string(&mut choice)
+            .context("failed to read Rofi's output")?;
+
+        let status = rofi.wait().context("failed to wait on Rofi")?;
+
+        Ok(status.success() && choice.trim_end() == "Allow")
+    }
+
+    use crate::daemon;
+    use crate::menu;
+    use anyhow::Context as _;
+    use std::io::Read as _;
+    use std::io::Write as _;
+    use std::process;
+}
+
 use ask_email::ask_email;
 mod ask_email {
-    pub(crate) fn ask_email() -> anyhow::Result<Option<String>> {
+    pub(crate) fn ask_email(display: &daemon::Display) -> anyhow::Result<Option<String>> {
         let mut email = String::new();
 
         let mut dmenu = process::Command::new("rofi");
         dmenu.arg("-dmenu").stdin(process::Stdio::null());
         dmenu.arg("-p").arg("Email address");
+        display.apply_to_rofi(&mut dmenu);
 
+        let _showing = menu::SHOWING.lock().unwrap();
         let outcome = run_dmenu(dmenu, &mut email).context("failed to prompt for email")?;
 
         if outcome == run_dmenu::Outcome::Cancelled || email.is_empty() {
@@ -477,6 +1400,8 @@ mod ask_email {
         Ok(Some(email))
     }
 
+    use crate::daemon;
+    use crate::menu;
     use crate::run_dmenu;
     use anyhow::Context as _;
     use std::process;
@@ -484,7 +1409,9 @@ mod ask_email {
 
 use ask_master_password::ask_master_password;
 mod ask_master_password {
+    /// Prompt on the given display, waiting for the menu or another prompt to close first.
     pub(crate) fn ask_master_password<'keybinds, Action>(
+        display: &daemon::Display,
         again: bool,
         status: &str,
         keybinds: &'keybinds [Keybind<Action>],
@@ -518,7 +1445,9 @@ mod ask_master_password {
         keybind::apply_to_command(&mut dmenu, keybinds);
 
         dmenu.arg("-password");
+        display.apply_to_rofi(&mut dmenu);
 
+        let _showing = menu::SHOWING.lock().unwrap();
         let outcome = run_dmenu(dmenu, &mut master_password)
             .context("failed to prompt for master password")?;
 
@@ -539,6 +1468,8 @@ mod ask_master_password {
         Custom(&'keybinds Action),
     }
 
+    use crate::daemon;
+    use crate::menu;
     use crate::run_dmenu;
     use anyhow::Context as _;
     use rofi_bw_common::keybind;
@@ -633,20 +1564,55 @@ mod error_reporting;
 
 mod auth;
 
+mod attachment;
+
+mod send;
+
+mod ssh_agent;
+
+mod vault;
+
+mod get;
+
+mod list;
+
+use status::Status;
+mod status;
+
+mod lock_events;
+
+use clipboard::Clipboard;
+mod clipboard;
+
+mod active_window;
+
+use usage::UsageFile;
+mod usage;
+
 use anyhow::Context as _;
-use arboard::Clipboard;
 use clap::Parser;
-use config::Config;
 use daemon::Daemon;
 use directories::ProjectDirs;
+use directories::UserDirs;
+use rofi_bw_common::default_copy::DefaultCopy;
 use rofi_bw_common::ipc;
+use rofi_bw_common::ipc::menu_request::CopyStep;
 use rofi_bw_common::ipc::menu_request::MenuState;
+use rofi_bw_common::menu_keybinds;
+use rofi_bw_common::usage::Sort;
+use rofi_bw_common::CipherString;
 use rofi_bw_common::CipherType;
 use rofi_bw_common::Keybind;
 use rofi_bw_common::List;
 use rofi_bw_util::fs;
 use rofi_bw_util::History;
+use std::borrow::Cow;
 use std::convert::Infallible;
-use std::env;
+use std::io;
+use std::io::Read as _;
+use std::io::Write as _;
+use std::iter;
 use std::process;
+use std::time::Duration;
 use uuid::Uuid;
+use zeroize::Zeroizing;
@@ -1,9 +1,17 @@
 pub(crate) fn run(
     lib_dir: &fs::path::List,
-    handshake: &ipc::Handshake<&MasterKey, &[u8], &History<ipc::View>>,
+    handshake: &ipc::Handshake<
+        &MasterKey,
+        &[u8],
+        &History<ipc::View>,
+        &usage::Record,
+        &menu_keybinds::Combinations,
+        &DefaultCopy,
+    >,
     rofi_options: &config::RofiOptions,
-    display: &str,
+    display: &Display,
     filter: &str,
+    locker: &Locker,
 ) -> anyhow::Result<ipc::MenuRequest> {
     let (parent_stream, child_stream) =
         UnixStream::pair().context("failed to create IPC channel")?;
@@ -14,18 +22,21 @@ pub(crate) fn run(
     rofi.arg("-show").arg("bw");
 
     apply_options(&mut rofi, rofi_options);
-    rofi.arg("-display").arg(display);
+    display.apply_to_rofi(&mut rofi);
     if !filter.is_empty() {
         rofi.arg("-filter").arg(filter);
     }
 
-    rofi_bw_common::keybind::apply_to_command(&mut rofi, rofi_bw_common::MENU_KEYBINDS);
+    let keybinds = handshake.keybinds.keybinds();
+    rofi_bw_common::keybind::apply_to_command(&mut rofi, &keybinds);
 
     let pipe_fd = child_stream.as_raw_fd();
     rofi.env(ipc::PIPE_FD_ENV_VAR, itoa::Buffer::new().format(pipe_fd));
     unsafe { rofi.pre_exec(move || unset_cloexec(pipe_fd)) };
 
+    let showing = SHOWING.lock().unwrap();
     let mut rofi = rofi.spawn().context("failed to spawn rofi")?;
+    locker.menu_opened(rofi.id());
 
     drop(child_stream);
 
@@ -40,12 +51,23 @@ pub(crate) fn run(
         Ok(ipc::menu_request::read(&mut pipe)?)
     })();
 
+    let exited = wait_without_reaping(&rofi);
+    let closed_to_lock = locker.menu_closed();
+    exited.context("failed to wait on rofi")?;
     let status = rofi.wait().context("failed to wait on rofi")?;
+    drop(showing);
+    if closed_to_lock {
+        return Ok(ipc::MenuRequest::Lock);
+    }
     anyhow::ensure!(status.success(), "rofi failed with {status}");
 
     ipc_result
 }
 
+/// Held while the menu or a prompt from another thread is open, so that they are shown one at a
+/// time instead of competing for the keyboard.
+pub(crate) static SHOWING: Mutex<()> = Mutex::new(());
+
 fn apply_options(rofi: &mut process::Command, rofi_options: &config::RofiOptions) {
     if rofi_options.threads != 0 {
         rofi.arg("-threads")
@@ -120,6 +142,21 @@ fn unset_cloexec(fd: RawFd) -> io::Result<()> {
     Ok(())
 }
 
+/// Wait for the process to exit, but leave it to be reaped later so that its process ID can’t be
+/// reused in the meantime.
+fn wait_without_reaping(child: &process::Child) -> io::Result<()> {
+    let mut info = unsafe { mem::zeroed::<libc::siginfo_t>() };
+    let flags = libc::WEXITED | libc::WNOWAIT;
+    loop {
+        match syscall_result(unsafe {
+            libc::waitid(libc::P_PID, child.id(), ptr::addr_of_mut!(info), flags)
+        }) {
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            res => return res.map(drop),
+        }
+    }
+}
+
 fn syscall_result(res: c_int) -> io::Result<c_int> {
     if res == -1 {
         Err(io::Error::last_os_error())
@@ -129,17 +166,25 @@ fn syscall_result(res: c_int) -> io::Result<c_int> {
 }
 
 use crate::config;
+use crate::daemon::Display;
+use crate::daemon::Locker;
 use anyhow::Context as _;
+use rofi_bw_common::default_copy::DefaultCopy;
 use rofi_bw_common::ipc;
+use rofi_bw_common::menu_keybinds;
+use rofi_bw_common::usage;
 use rofi_bw_common::MasterKey;
 use rofi_bw_util::fs;
 use rofi_bw_util::History;
 use std::io;
 use std::io::BufReader;
 use std::io::BufWriter;
+use std::mem;
 use std::os::raw::c_int;
 use std::os::unix::io::AsRawFd;
 use std::os::unix::io::RawFd;
 use std::os::unix::net::UnixStream;
 use std::os::unix::process::CommandExt;
 use std::process;
+use std::ptr;
+use std::sync::Mutex;
@@ -0,0 +1,149 @@
+#[derive(bincode::Encode, bincode::Decode)]
+pub(crate) struct Request {
+    pub(crate) name: String,
+    pub(crate) content: Content,
+    /// How long until the Send is deleted; uses the configured default if `None`.
+    pub(crate) deletion: Option<Duration>,
+    /// How long until the Send expires; uses the configured default if `None`.
+    pub(crate) expiry: Option<Duration>,
+    /// Uses the configured default if `None`.
+    pub(crate) max_access_count: Option<u32>,
+    pub(crate) password: Option<String>,
+}
+
+impl Request {
+    /// A text Send that uses the configured defaults.
+    pub(crate) fn text(name: String, text: String, hidden: bool) -> Self {
+        Self {
+            name,
+            content: Content::Text { text, hidden },
+            deletion: None,
+            expiry: None,
+            max_access_count: None,
+            password: None,
+        }
+    }
+}
+
+#[derive(bincode::Encode, bincode::Decode)]
+pub(crate) enum Content {
+    Text {
+        text: String,
+        /// Whether the text should be hidden from the recipient by default.
+        hidden: bool,
+    },
+    File {
+        file_name: String,
+        data: Vec<u8>,
+    },
+}
+
+/// Create a Send, returning the link that it can be accessed at.
+pub(crate) fn create(
+    session: &mut Session<'_, '_>,
+    defaults: &config::Send,
+    request: &Request,
+) -> Result<String, CreateError> {
+    let user_key = session.user_key().map_err(CreateError::UserKey)?;
+
+    let rng = &mut rand::thread_rng();
+    let key_material = Zeroizing::new(rng.gen::<[u8; send::KEY_MATERIAL_LEN]>());
+    let key = SymmetricKey::for_send(&*key_material);
+
+    let encrypt = |s: &str| CipherString::encrypt(&key, &mut rand::thread_rng(), &s.to_owned());
+
+    let now = OffsetDateTime::now_utc();
+    let timestamp = |after: Duration| {
+        (now + after)
+            .format(&Rfc3339)
+            .expect("dates in the near future are formattable")
+    };
+
+    let password = request.password.as_ref().map(|password| {
+        let mut hash = [0; 32];
+        pbkdf2::<Hmac<Sha256>>(password.as_bytes(), &*key_material, 100_000, &mut hash);
+        base64::encode(hash)
+    });
+
+    let mut body = bitwarden_api::SendBody {
+        r#type: 0,
+        name: encrypt(&request.name).to_string(),
+        notes: None,
+        key: CipherString::encrypt(&user_key, rng, &key_material.to_vec()).to_string(),
+        max_access_count: request.max_access_count.or(defaults.max_access_count),
+        expiration_date: request.expiry.or(defaults.expiry).map(timestamp),
+        deletion_date: timestamp(request.deletion.unwrap_or(defaults.deletion)),
+        text: None,
+        file: None,
+        file_length: None,
+        password,
+        disabled: false,
+        hide_email: false,
+    };
+
+    let file_data = match &request.content {
+        Content::Text { text, hidden } => {
+            body.text = Some(bitwarden_api::SendText {
+                text: encrypt(text).to_string(),
+                hidden: *hidden,
+            });
+            None
+        }
+        Content::File { file_name, data } => {
+            let encrypted = cipher_string::Untyped::encrypt(&key, rng, data).to_buffer();
+            body.r#type = 1;
+            body.file = Some(bitwarden_api::SendFile {
+                file_name: encrypt(file_name).to_string(),
+            });
+            body.file_length = Some(encrypted.len() as u64);
+            Some(encrypted)
+        }
+    };
+
+    let access_id = session
+        .create_send(&body, file_data.as_deref())
+        .map_err(CreateError::Create)?;
+
+    Ok(send::link(&access_id, &*key_material))
+}
+
+#[derive(Debug)]
+pub(crate) enum CreateError {
+    UserKey(session::UserKeyError),
+    Create(session::CreateSendError),
+}
+
+impl Display for CreateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("failed to create Send")
+    }
+}
+
+impl std::error::Error for CreateError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::UserKey(e) => Some(e),
+            Self::Create(e) => Some(e),
+        }
+    }
+}
+
+use crate::bitwarden_api;
+use crate::config;
+use crate::session;
+use crate::Session;
+use hmac::Hmac;
+use pbkdf2::pbkdf2;
+use rand::Rng as _;
+use rofi_bw_common::cipher_string;
+use rofi_bw_common::send;
+use rofi_bw_common::CipherString;
+use rofi_bw_common::SymmetricKey;
+use sha2::Sha256;
+use std::fmt;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::time::Duration;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use zeroize::Zeroizing;
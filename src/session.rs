@@ -6,6 +6,8 @@ pub(crate) struct Session<'http, 'client_id> {
     master_key: MasterKey,
     token: auth::Token,
     account_data: String,
+    synced_at: SystemTime,
+    unlocked_at: Instant,
 }
 
 impl<'http, 'client_id> Session<'http, 'client_id> {
@@ -68,6 +70,8 @@ impl<'http, 'client_id> Session<'http, 'client_id> {
             master_key,
             token,
             account_data,
+            synced_at: SystemTime::now(),
+            unlocked_at: Instant::now(),
         })
     }
 
@@ -90,10 +94,105 @@ impl<'http, 'client_id> Session<'http, 'client_id> {
         self.token.set_expired();
 
         self.account_data = self.client()?.sync()?;
+        self.synced_at = SystemTime::now();
 
         Ok(())
     }
 
+    pub(crate) fn edit_folder(&mut self, edit: &FolderEdit) -> Result<(), EditFolderError> {
+        match edit {
+            FolderEdit::Create { name } => {
+                let name = self.encrypt_folder_name(name)?;
+                self.client()?.create_folder(&name)?;
+            }
+            FolderEdit::Rename { id, name } => {
+                let name = self.encrypt_folder_name(name)?;
+                self.client()?.rename_folder(Uuid::from_bytes(*id), &name)?;
+            }
+            FolderEdit::Delete { id } => self.client()?.delete_folder(Uuid::from_bytes(*id))?,
+        }
+        Ok(())
+    }
+
+    fn encrypt_folder_name(&self, name: &str) -> Result<String, EditFolderError> {
+        let key = self.user_key().map_err(EditFolderError::UserKey)?;
+        let encrypted = CipherString::encrypt(&key, &mut rand::thread_rng(), &name.to_owned());
+        Ok(encrypted.to_string())
+    }
+
+    pub(crate) fn edit_send(&mut self, edit: &SendEdit) -> Result<(), EditSendError> {
+        match *edit {
+            SendEdit::SetDisabled { id, disabled } => {
+                let id = Uuid::from_bytes(id);
+                // Updating a Send requires sending all of it, so reuse what we got from the server.
+                let mut send = self.find_send(id)?;
+                send["disabled"] = serde_json::Value::Bool(disabled);
+                // The server gives us the hash of the password; sending it back would set the
+                // password to that hash. Leaving it out keeps the existing password.
+                send["password"] = serde_json::Value::Null;
+                self.client()?.update_send(id, &send)?;
+            }
+            SendEdit::Delete { id } => self.client()?.delete_send(Uuid::from_bytes(id))?,
+        }
+        Ok(())
+    }
+
+    fn find_send(&self, id: Uuid) -> Result<serde_json::Value, EditSendError> {
+        #[derive(Deserialize)]
+        struct AccountData {
+            sends: Vec<serde_json::Value>,
+        }
+
+        let data = serde_json::from_str::<AccountData>(&self.account_data)
+            .map_err(EditSendError::Parse)?;
+        data.sends
+            .into_iter()
+            .find(|send| send["id"].as_str().and_then(|s| s.parse::<Uuid>().ok()) == Some(id))
+            .ok_or(EditSendError::NotFound)
+    }
+
+    pub(crate) fn download_attachment(
+        &mut self,
+        cipher_id: Uuid,
+        attachment_id: &str,
+    ) -> Result<Vec<u8>, DownloadAttachmentError> {
+        Ok(self
+            .client()?
+            .download_attachment(cipher_id, attachment_id)?)
+    }
+
+    /// Create a Send, uploading the file contents for file Sends. Returns the Send’s access ID.
+    pub(crate) fn create_send(
+        &mut self,
+        body: &bitwarden_api::SendBody,
+        file_data: Option<&[u8]>,
+    ) -> Result<String, CreateSendError> {
+        let client = self.client()?;
+        Ok(match file_data {
+            Some(data) => client.create_file_send(body, data)?,
+            None => client.create_send(body)?,
+        })
+    }
+
+    /// Decrypt the user’s symmetric key, which is stored in the account data.
+    pub(crate) fn user_key(&self) -> Result<SymmetricKey, UserKeyError> {
+        #[derive(Deserialize)]
+        struct AccountData {
+            profile: Profile,
+        }
+        #[derive(Deserialize)]
+        struct Profile {
+            key: CipherString<SymmetricKey>,
+        }
+
+        let data =
+            serde_json::from_str::<AccountData>(&self.account_data).map_err(UserKeyError::Parse)?;
+        data.profile
+            .key
+            .decrypt(&self.master_key)
+            .map_err(UserKeyError::Decrypt)
+    }
+
     pub(crate) fn is_correct_master_password(&self, master_password: &str) -> bool {
         auth::master_key(&self.prelogin, &self.email, master_password) == self.master_key
     }
@@ -105,6 +204,19 @@ impl<'http, 'client_id> Session<'http, 'client_id> {
     pub(crate) fn account_data(&self) -> &str {
         &self.account_data
     }
+
+    pub(crate) fn email(&self) -> &str {
+        &self.email
+    }
+
+    pub(crate) fn unlocked_at(&self) -> Instant {
+        self.unlocked_at
+    }
+
+    /// When the account data was last downloaded.
+    pub(crate) fn synced_at(&self) -> SystemTime {
+        self.synced_at
+    }
 }
 
 #[derive(Debug)]
@@ -165,13 +277,184 @@ impl std::error::Error for ResyncError {
     }
 }
 
+#[derive(Debug)]
+pub(crate) enum EditFolderError {
+    UserKey(UserKeyError),
+    Refresh(auth::refresh::Error),
+    Edit(bitwarden_api::EditFolderError),
+}
+
+impl From<auth::refresh::Error> for EditFolderError {
+    fn from(error: auth::refresh::Error) -> Self {
+        Self::Refresh(error)
+    }
+}
+
+impl From<bitwarden_api::EditFolderError> for EditFolderError {
+    fn from(error: bitwarden_api::EditFolderError) -> Self {
+        Self::Edit(error)
+    }
+}
+
+impl Display for EditFolderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("failed to edit folder")
+    }
+}
+
+impl std::error::Error for EditFolderError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::UserKey(e) => Some(e),
+            Self::Refresh(e) => Some(e),
+            Self::Edit(e) => Some(e),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) enum EditSendError {
+    Refresh(auth::refresh::Error),
+    Parse(serde_json::Error),
+    NotFound,
+    Edit(bitwarden_api::EditSendError),
+}
+
+impl From<auth::refresh::Error> for EditSendError {
+    fn from(error: auth::refresh::Error) -> Self {
+        Self::Refresh(error)
+    }
+}
+
+impl From<bitwarden_api::EditSendError> for EditSendError {
+    fn from(error: bitwarden_api::EditSendError) -> Self {
+        Self::Edit(error)
+    }
+}
+
+impl Display for EditSendError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound => f.write_str("Send no longer exists"),
+            _ => f.write_str("failed to edit Send"),
+        }
+    }
+}
+
+impl std::error::Error for EditSendError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Refresh(e) => Some(e),
+            Self::Parse(e) => Some(e),
+            Self::NotFound => None,
+            Self::Edit(e) => Some(e),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) enum DownloadAttachmentError {
+    Refresh(auth::refresh::Error),
+    Download(bitwarden_api::DownloadAttachmentError),
+}
+
+impl From<auth::refresh::Error> for DownloadAttachmentError {
+    fn from(error: auth::refresh::Error) -> Self {
+        Self::Refresh(error)
+    }
+}
+
+impl From<bitwarden_api::DownloadAttachmentError> for DownloadAttachmentError {
+    fn from(error: bitwarden_api::DownloadAttachmentError) -> Self {
+        Self::Download(error)
+    }
+}
+
+impl Display for DownloadAttachmentError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("failed to download attachment")
+    }
+}
+
+impl std::error::Error for DownloadAttachmentError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Refresh(e) => Some(e),
+            Self::Download(e) => Some(e),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) enum CreateSendError {
+    Refresh(auth::refresh::Error),
+    Create(bitwarden_api::CreateSendError),
+}
+
+impl From<auth::refresh::Error> for CreateSendError {
+    fn from(error: auth::refresh::Error) -> Self {
+        Self::Refresh(error)
+    }
+}
+
+impl From<bitwarden_api::CreateSendError> for CreateSendError {
+    fn from(error: bitwarden_api::CreateSendError) -> Self {
+        Self::Create(error)
+    }
+}
+
+impl Display for CreateSendError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("failed to create Send")
+    }
+}
+
+impl std::error::Error for CreateSendError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Refresh(e) => Some(e),
+            Self::Create(e) => Some(e),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) enum UserKeyError {
+    Parse(serde_json::Error),
+    Decrypt(cipher_string::DecryptSymmetricKeyError),
+}
+
+impl Display for UserKeyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("failed to get user key from account data")
+    }
+}
+
+impl std::error::Error for UserKeyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Parse(e) => Some(e),
+            Self::Decrypt(e) => Some(e),
+        }
+    }
+}
+
 use crate::auth;
 use crate::auth::Prelogin;
 use crate::bitwarden_api;
 use crate::cache;
 use crate::cache::CacheRef;
+use rofi_bw_common::cipher_string;
+use rofi_bw_common::ipc::menu_request::FolderEdit;
+use rofi_bw_common::ipc::menu_request::SendEdit;
+use rofi_bw_common::CipherString;
 use rofi_bw_common::MasterKey;
+use rofi_bw_common::SymmetricKey;
 use rofi_bw_util::fs;
+use serde::Deserialize;
 use std::fmt;
 use std::fmt::Display;
 use std::fmt::Formatter;
+use std::time::Instant;
+use std::time::SystemTime;
+use uuid::Uuid;
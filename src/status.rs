@@ -0,0 +1,58 @@
+//! The output of `rofi-bw status`.
+
+#[derive(Serialize)]
+pub(crate) struct Status {
+    pub(crate) running: bool,
+    /// The email of the logged-in account, if any.
+    pub(crate) email: Option<String>,
+    pub(crate) unlocked: bool,
+    /// Whether the daemon is showing Rofi or otherwise handling a request, in which case it refuses
+    /// other requests.
+    pub(crate) busy: bool,
+    /// The number of seconds until the vault automatically locks; `None` if it never will, or if
+    /// there is no maximum session age and the idle countdown is paused because the menu is open.
+    pub(crate) auto_lock_seconds: Option<u64>,
+    /// When the vault was last synced, in RFC 3339 format.
+    pub(crate) last_sync: Option<String>,
+}
+
+impl Status {
+    pub(crate) fn not_running(email: Option<String>) -> Self {
+        Self {
+            running: false,
+            email,
+            unlocked: false,
+            busy: false,
+            auto_lock_seconds: None,
+            last_sync: None,
+        }
+    }
+
+    pub(crate) fn format(&self, json: bool) -> String {
+        if json {
+            let mut output = serde_json::to_string(self).unwrap();
+            output.push('\n');
+            return output;
+        }
+
+        let mut output = String::new();
+        let yes_no = |b| if b { "yes" } else { "no" };
+        writeln!(output, "Running: {}", yes_no(self.running)).unwrap();
+        writeln!(output, "Email: {}", self.email.as_deref().unwrap_or("none")).unwrap();
+        writeln!(output, "Unlocked: {}", yes_no(self.unlocked)).unwrap();
+        if self.running {
+            writeln!(output, "Busy: {}", yes_no(self.busy)).unwrap();
+        }
+        if let Some(seconds) = self.auto_lock_seconds {
+            let (minutes, seconds) = (seconds / 60, seconds % 60);
+            writeln!(output, "Auto-lock in: {minutes}m {seconds}s").unwrap();
+        }
+        if let Some(last_sync) = &self.last_sync {
+            writeln!(output, "Last sync: {last_sync}").unwrap();
+        }
+        output
+    }
+}
+
+use serde::Serialize;
+use std::fmt::Write as _;
@@ -0,0 +1,79 @@
+//! The record of which items have been copied from or opened, kept in the data directory for
+//! `sort = "frecency"` and the list of recently used items.
+
+pub(crate) struct UsageFile {
+    record: usage::Record,
+    path: fs::PathBuf,
+}
+
+impl UsageFile {
+    /// Load the record, starting a new one if it is missing or can’t be read.
+    pub(crate) fn load(data_dir: &fs::Path) -> Self {
+        let path = data_dir.join("usage");
+        let record = load_record(&path).unwrap_or_else(|e| {
+            eprintln!("Warning: {:?}", e.context("failed to load usage record"));
+            usage::Record::default()
+        });
+        Self { record, path }
+    }
+
+    pub(crate) fn record(&self) -> &usage::Record {
+        &self.record
+    }
+
+    pub(crate) fn record_use(&mut self, key: &SymmetricKey, cipher_id: [u8; 16]) {
+        let item = usage::ItemKey::new(key, cipher_id);
+        self.record.record_use(item, usage::now());
+        if let Err(e) = self.store() {
+            eprintln!("Warning: {e:?}");
+        }
+    }
+
+    pub(crate) fn record_opens(&mut self, key: &SymmetricKey, cipher_ids: &[[u8; 16]]) {
+        for &cipher_id in cipher_ids {
+            self.record.record_open(usage::ItemKey::new(key, cipher_id));
+        }
+        if let Err(e) = self.store() {
+            eprintln!("Warning: {e:?}");
+        }
+    }
+
+    fn store(&self) -> anyhow::Result<()> {
+        let mut buf = vec![versions::V0];
+        bincode::encode_into_std_write(&self.record, &mut buf, bincode::config::standard())
+            .context("failed to encode usage record")?;
+        fs::overwrite::with(&*self.path, &buf).context("failed to write usage record")?;
+        Ok(())
+    }
+}
+
+fn load_record(path: &fs::Path) -> anyhow::Result<usage::Record> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(fs::read::Error {
+            kind: fs::read::ErrorKind::Open(e),
+            ..
+        }) if e.source.kind() == io::ErrorKind::NotFound => return Ok(usage::Record::default()),
+        Err(e) => return Err(e.into()),
+    };
+
+    match &*bytes {
+        [versions::V0, rest @ ..] => {
+            let (record, _) = bincode::decode_from_slice(rest, bincode::config::standard())
+                .context("usage record is invalid")?;
+            Ok(record)
+        }
+        &[version, ..] => anyhow::bail!("unknown version {version} in usage record"),
+        [] => anyhow::bail!("usage record is empty"),
+    }
+}
+
+mod versions {
+    pub(crate) const V0: u8 = 0;
+}
+
+use anyhow::Context as _;
+use rofi_bw_common::usage;
+use rofi_bw_common::SymmetricKey;
+use rofi_bw_util::fs;
+use std::io;
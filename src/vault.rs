@@ -0,0 +1,239 @@
+//! The vault as seen by the daemon, for features that work without the menu.
+//!
+//! Unlike the plugin, which decrypts everything up front, values here are left encrypted until
+//! they are needed.
+
+pub(crate) struct Vault {
+    pub(crate) key: SymmetricKey,
+    /// Items that are in the trash are left out.
+    pub(crate) ciphers: Vec<Cipher>,
+    pub(crate) folders: Vec<Folder>,
+    pub(crate) domains: Domains,
+}
+
+impl Vault {
+    pub(crate) fn load(session: &Session<'_, '_>) -> anyhow::Result<Self> {
+        Self::parse(session.user_key()?, session.account_data())
+    }
+
+    fn parse(key: SymmetricKey, account_data: &str) -> anyhow::Result<Self> {
+        #[derive(Deserialize)]
+        struct AccountData {
+            #[serde(deserialize_with = "lenient")]
+            ciphers: Vec<Cipher>,
+            #[serde(deserialize_with = "lenient")]
+            folders: Vec<Folder>,
+            /// Missing from data synced by older versions of rofi-bw.
+            #[serde(default)]
+            domains: Option<Domains>,
+        }
+
+        let mut data = serde_json::from_str::<AccountData>(account_data)
+            .context("failed to parse account data")?;
+
+        data.ciphers.retain(|cipher| cipher.deleted_date.is_none());
+
+        Ok(Self {
+            key,
+            ciphers: data.ciphers,
+            folders: data.folders,
+            domains: data.domains.unwrap_or_default(),
+        })
+    }
+}
+
+/// Parse each item on its own, leaving out those that can’t be parsed with a warning, so that one
+/// item this version doesn’t understand doesn’t stop the rest from being used.
+fn lenient<'de, D: Deserializer<'de>, T: DeserializeOwned>(
+    deserializer: D,
+) -> Result<Vec<T>, D::Error> {
+    let values = Vec::<serde_json::Value>::deserialize(deserializer)?;
+    let items = values
+        .into_iter()
+        .filter_map(|value| match T::deserialize(&value) {
+            Ok(item) => Some(item),
+            Err(e) => {
+                let id = value.get("id").and_then(serde_json::Value::as_str);
+                let e = anyhow!(e).context(format!("skipping item {}", id.unwrap_or("without ID")));
+                eprintln!("Warning: {e:?}");
+                None
+            }
+        });
+    Ok(items.collect())
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Cipher {
+    pub(crate) id: Uuid,
+    r#type: u8,
+    pub(crate) folder_id: Option<Uuid>,
+    pub(crate) favorite: bool,
+    pub(crate) name: CipherString<String>,
+    pub(crate) notes: Option<CipherString<String>>,
+    deleted_date: Option<de::IgnoredAny>,
+    reprompt: u8,
+    pub(crate) login: Option<Login>,
+    pub(crate) fields: Option<Vec<Field>>,
+}
+
+impl Cipher {
+    /// `None` for types newer than this version of rofi-bw.
+    pub(crate) fn cipher_type(&self) -> Option<CipherType> {
+        Some(match self.r#type {
+            1 => CipherType::Login,
+            2 => CipherType::SecureNote,
+            3 => CipherType::Card,
+            4 => CipherType::Identity,
+            5 => CipherType::SshKey,
+            _ => return None,
+        })
+    }
+
+    /// Whether the master password has to be entered again to access the item’s secrets.
+    pub(crate) fn reprompt(&self) -> bool {
+        self.reprompt != 0
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Login {
+    pub(crate) username: Option<CipherString<String>>,
+    pub(crate) password: Option<CipherString<String>>,
+    pub(crate) totp: Option<CipherString<String>>,
+    pub(crate) uris: Option<Vec<Uri>>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct Uri {
+    pub(crate) uri: Option<CipherString<String>>,
+    #[serde(rename = "match")]
+    pub(crate) match_type: Option<UriMatchType>,
+}
+
+/// A custom field.
+#[derive(Deserialize)]
+pub(crate) struct Field {
+    pub(crate) name: Option<CipherString<String>>,
+    pub(crate) value: Option<CipherString<String>>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct Folder {
+    pub(crate) id: Uuid,
+    pub(crate) name: CipherString<String>,
+}
+
+/// A small vault for testing `get` and `list` against.
+#[cfg(test)]
+pub(crate) fn test_vault() -> Vault {
+    let key = SymmetricKey(zeroize::Zeroizing::new([7; SymmetricKey::LEN]));
+    let other_key = SymmetricKey(zeroize::Zeroizing::new([8; SymmetricKey::LEN]));
+    let encrypt = |key: &SymmetricKey, value: &str| {
+        CipherString::encrypt(key, &mut rand::thread_rng(), &value.to_owned()).to_string()
+    };
+    let e = |value: &str| encrypt(&key, value);
+
+    let login = |id: &str, folder_id: Option<&str>, name: &str, username: &str, uri: &str| {
+        serde_json::json!({
+            "id": id,
+            "type": 1,
+            "folderId": folder_id,
+            "favorite": false,
+            "name": e(name),
+            "notes": null,
+            "deletedDate": null,
+            "reprompt": 0,
+            "login": {
+                "username": e(username),
+                "password": e(&format!("{name} password")),
+                "totp": null,
+                "uris": [{ "uri": e(uri), "match": null }],
+            },
+            "fields": null,
+        })
+    };
+
+    let work = "00000000-0000-0000-0000-0000000000f1";
+    let mut bank = login(
+        "00000000-0000-0000-0000-000000000003",
+        None,
+        "Bank",
+        "carol",
+        "https://bank.example",
+    );
+    bank["reprompt"] = 1.into();
+    bank["fields"] = serde_json::json!([{ "name": e("PIN"), "value": e("1234") }]);
+    let mut deleted = login(
+        "00000000-0000-0000-0000-000000000005",
+        None,
+        "Old GitHub",
+        "alice",
+        "https://github.com",
+    );
+    deleted["deletedDate"] = "2026-01-01T00:00:00Z".into();
+    let mut undecryptable = login(
+        "00000000-0000-0000-0000-000000000006",
+        None,
+        "Undecryptable",
+        "dave",
+        "https://example.com",
+    );
+    undecryptable["name"] = encrypt(&other_key, "Undecryptable").into();
+
+    let account_data = serde_json::json!({
+        "folders": [
+            { "id": work, "name": e("Work") },
+            { "id": "00000000-0000-0000-0000-0000000000f2", "name": "not a cipher string" },
+        ],
+        "ciphers": [
+            login(
+                "00000000-0000-0000-0000-000000000001",
+                Some(work),
+                "GitHub",
+                "alice",
+                "https://github.com/login",
+            ),
+            login(
+                "00000000-0000-0000-0000-000000000002",
+                None,
+                "GitHub Gist",
+                "bob",
+                "https://gist.github.com",
+            ),
+            bank,
+            {
+                "id": "00000000-0000-0000-0000-000000000004",
+                "type": 2,
+                "folderId": null,
+                "favorite": true,
+                "name": e("Recipe"),
+                "notes": e("Flour and water"),
+                "deletedDate": null,
+                "reprompt": 0,
+                "login": null,
+                "fields": null,
+            },
+            deleted,
+            undecryptable,
+            { "id": "00000000-0000-0000-0000-000000000007", "type": 1 },
+        ],
+    });
+
+    Vault::parse(key, &account_data.to_string()).unwrap()
+}
+
+use crate::Session;
+use anyhow::anyhow;
+use anyhow::Context as _;
+use rofi_bw_common::domains::Domains;
+use rofi_bw_common::uri_match::UriMatchType;
+use rofi_bw_common::CipherString;
+use rofi_bw_common::CipherType;
+use rofi_bw_common::SymmetricKey;
+use serde::de;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde::Deserializer;
+use uuid::Uuid;
@@ -364,6 +364,10 @@ pub mod file {
                 }
             }
 
+            if let Some(mode) = options.mode {
+                std_options.mode(mode);
+            }
+
             let std_file = std_options.open(path.borrow()).map_err(|source| Error {
                 path: path.borrow().into(),
                 options,
@@ -384,12 +388,22 @@ pub mod file {
         #[non_exhaustive]
         pub struct Options {
             pub access: Access,
+            /// The Unix permission bits to give the file if it is created.
+            pub mode: Option<u32>,
         }
 
         impl Options {
             #[must_use]
             pub fn from_access(access: Access) -> Self {
-                Self { access }
+                Self { access, mode: None }
+            }
+
+            #[must_use]
+            pub fn mode(self, mode: u32) -> Self {
+                Self {
+                    mode: Some(mode),
+                    ..self
+                }
             }
         }
 
@@ -470,6 +484,7 @@ pub mod file {
         use std::fmt::Display;
         use std::fmt::Formatter;
         use std::io;
+        use std::os::unix::fs::OpenOptionsExt as _;
     }
 
     use crate::fs;